@@ -1,4 +1,171 @@
+//! Generates the register tables of `src/register_map.rs` and
+//! `src/register_dump.rs` from `device.yaml`, so they cannot drift from the
+//! generated low-level API.
+
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde_yaml::{Mapping, Value};
+
+/// Write-one-to-clear bits, which `device.yaml` has no way to express.
+/// Registers whose name starts with `IrqStatus` are write-one-to-clear as a
+/// whole; these are the flags inside otherwise read-write registers.
+const WRITE_ONE_TO_CLEAR_FIELDS: &[(&str, &str)] = &[("TimerControl", "timeout_flag")];
+
+/// A register of `device.yaml` with refs resolved and repeats expanded.
+struct Register {
+    name: String,
+    /// Generated field set type holding the register's value.
+    field_set: String,
+    address: u8,
+    size_bytes: u8,
+    access: &'static str,
+    reset_value: Option<u64>,
+    read_only_mask: u8,
+    write_one_to_clear_mask: u8,
+    /// `(name, start, end)`, most significant first.
+    fields: Vec<(String, u8, u8)>,
+}
+
+fn mask(start: u8, end: u8) -> u64 {
+    ((1u64 << (end - start)) - 1) << start
+}
+
+fn registers(manifest: &Mapping) -> Vec<Register> {
+    let int = |value: &Value| value.as_u64().expect("integer");
+    let mut registers = Vec::new();
+    for (name, entry) in manifest {
+        let name = name.as_str().expect("object name");
+        if name == "config" {
+            continue;
+        }
+        let mut register = entry.as_mapping().expect("object").clone();
+        let mut field_set = name.to_string();
+        if register["type"] == "ref" {
+            let target = register["target"].as_str().expect("ref target").to_string();
+            let overrides = register["override"]
+                .as_mapping()
+                .expect("ref override")
+                .clone();
+            register = manifest[target.as_str()]
+                .as_mapping()
+                .expect("ref target")
+                .clone();
+            register.extend(overrides);
+            field_set = target;
+        }
+        assert_eq!(register["type"], "register", "{name} is not a register");
+
+        let mut fields = Vec::new();
+        let mut read_only_mask = 0;
+        let mut write_one_to_clear_mask = 0;
+        for (field_name, field) in register["fields"].as_mapping().expect("fields") {
+            let field_name = field_name.as_str().expect("field name");
+            let start = int(&field["start"]) as u8;
+            let end = field.get("end").map_or(start + 1, |end| int(end) as u8);
+            if field.get("access").is_some_and(|access| access == "RO") {
+                read_only_mask |= mask(start, end) as u8;
+            }
+            if WRITE_ONE_TO_CLEAR_FIELDS.contains(&(name, field_name)) {
+                write_one_to_clear_mask |= mask(start, end) as u8;
+            }
+            fields.push((field_name.to_string(), start, end));
+        }
+        fields.sort_by_key(|&(_, start, _)| std::cmp::Reverse(start));
+
+        let access = match (register["access"].as_str(), name.starts_with("IrqStatus")) {
+            (Some("RO"), _) => "ReadOnly",
+            (_, true) => "WriteOneToClear",
+            (_, false) => "ReadWrite",
+        };
+        let address = int(&register["address"]) as u8;
+        let (count, stride) = match register.get("repeat") {
+            Some(repeat) => (int(&repeat["count"]), int(&repeat["stride"]) as u8),
+            None => (1, 0),
+        };
+        for index in 0..count {
+            registers.push(Register {
+                name: if count > 1 {
+                    format!("{name}{index}")
+                } else {
+                    name.to_string()
+                },
+                field_set: field_set.clone(),
+                address: address + index as u8 * stride,
+                size_bytes: (int(&register["size_bits"]) / 8) as u8,
+                access,
+                reset_value: register.get("reset_value").map(int),
+                read_only_mask,
+                write_one_to_clear_mask,
+                fields: fields.clone(),
+            });
+        }
+    }
+    registers.sort_by_key(|register| register.address);
+    registers
+}
+
 fn main() {
-    println!("cargo:rustc-link-arg=-Tlinkall.x");
-    println!("cargo:rustc-link-arg=-Tdefmt.x");
+    println!("cargo:rerun-if-changed=device.yaml");
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let manifest: Mapping =
+        serde_yaml::from_str(&std::fs::read_to_string("device.yaml").expect("read device.yaml"))
+            .expect("parse device.yaml");
+    let registers = registers(&manifest);
+
+    let mut table = String::from("[\n");
+    let mut fields = String::from("match address {\n");
+    let mut decode = String::from("decode_table! {\n");
+    for register in &registers {
+        let reset_value = match register.reset_value {
+            Some(value) => format!("Some({value:#04X})"),
+            None => "None".to_string(),
+        };
+        write!(
+            table,
+            "    RegisterInfo::new({:?}, {:#04X}, {}, RegisterAccess::{}, {reset_value}, {:#04X})",
+            register.name,
+            register.address,
+            register.size_bytes,
+            register.access,
+            register.read_only_mask
+        )
+        .unwrap();
+        if register.write_one_to_clear_mask != 0 {
+            write!(
+                table,
+                ".with_write_one_to_clear_mask({:#04X})",
+                register.write_one_to_clear_mask
+            )
+            .unwrap();
+        }
+        table.push_str(",\n");
+
+        write!(fields, "    {:#04X} => &[", register.address).unwrap();
+        for (name, start, end) in &register.fields {
+            write!(fields, "field!({name:?}, {start}, {end}), ").unwrap();
+        }
+        fields.push_str("],\n");
+
+        writeln!(
+            decode,
+            "    {:#04X} => {},",
+            register.address, register.field_set
+        )
+        .unwrap();
+    }
+    table.push(']');
+    fields.push_str("    _ => &[],\n}");
+    decode.push('}');
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR");
+    let out_dir = Path::new(&out_dir);
+    let write = |file: &str, contents: String| {
+        std::fs::write(out_dir.join(file), contents).expect("write generated table");
+    };
+    write("register_count.rs", registers.len().to_string());
+    write("registers.rs", table);
+    write("register_fields.rs", fields);
+    write("register_decode.rs", decode);
 }
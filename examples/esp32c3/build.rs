@@ -0,0 +1,4 @@
+fn main() {
+    println!("cargo:rustc-link-arg-bins=-Tlinkall.x");
+    println!("cargo:rustc-link-arg-bins=-Tdefmt.x");
+}
@@ -1,3 +1,5 @@
+use crate::units::{DeciCelsius, Microamps, Microwatts, Millivolts};
+
 /// Helper to extract a 12-bit ADC value from a raw u16 read over two 8-bit registers.
 /// Assumes Big Endian read: raw_u16 = (MSB_register_byte << 8) | LSB_register_byte.
 /// MSB_register_byte contains ADC[11:4].
@@ -24,3 +26,146 @@ pub(crate) fn adc_13bit_from_raw_u16(raw_be_u16: u16) -> u16 {
 pub(crate) fn adc_24bit_from_raw_u32(raw_be_u32: u32) -> u32 {
     raw_be_u32 & 0x00FFFFFF // Ensure only the lower 24 bits are used
 }
+
+// Channel scaling from the datasheet ADC table (section 9.7), applied to the
+// extracted 12/13/24-bit ADC codes. The integer versions round to the nearest
+// unit; the `f32` versions are kept behind the `float` feature.
+
+/// Scales an ADC code by an LSB given in tenths of a millivolt, rounding to the nearest mV.
+fn scale_tenths_mv(adc_val: u16, lsb_tenths_mv: u32) -> Millivolts {
+    Millivolts((adc_val as u32 * lsb_tenths_mv + 5) / 10)
+}
+
+pub(crate) fn acin_voltage_from_adc(adc_val: u16) -> Millivolts {
+    scale_tenths_mv(adc_val, 17)
+}
+
+pub(crate) fn acin_current_from_adc(adc_val: u16) -> Microamps {
+    Microamps(adc_val as i32 * 625)
+}
+
+pub(crate) fn vbus_voltage_from_adc(adc_val: u16) -> Millivolts {
+    scale_tenths_mv(adc_val, 17)
+}
+
+pub(crate) fn vbus_current_from_adc(adc_val: u16) -> Microamps {
+    Microamps(adc_val as i32 * 375)
+}
+
+pub(crate) fn internal_temperature_from_adc(adc_val: u16) -> DeciCelsius {
+    DeciCelsius(adc_val as i16 - 1447)
+}
+
+pub(crate) fn ts_pin_voltage_from_adc(adc_val: u16) -> Millivolts {
+    scale_tenths_mv(adc_val, 8)
+}
+
+/// Voltage at the bottom of a GPIO ADC input range.
+pub(crate) const fn gpio_adc_offset_mv(range: crate::GpioAdcRange) -> u16 {
+    match range {
+        crate::GpioAdcRange::Range00To20475V => 0,
+        crate::GpioAdcRange::Range07To27475V => 700,
+    }
+}
+
+pub(crate) fn gpio_voltage_from_adc(adc_val: u16, range: crate::GpioAdcRange) -> Millivolts {
+    Millivolts(scale_tenths_mv(adc_val, 5).0 + gpio_adc_offset_mv(range) as u32)
+}
+
+/// Highest GPIO1 ADC IRQ threshold above the bottom of the input range, 0xFF * 8mV.
+pub(crate) const GPIO1_ADC_THRESHOLD_SPAN_MV: u16 = 2040;
+
+/// GPIO1 ADC IRQ threshold register value for `threshold_mv`, rounded to 8mV.
+/// The threshold compares against the ADC code, so it moves with the input range.
+pub(crate) const fn gpio1_adc_threshold_raw_from_mv(
+    threshold_mv: u16,
+    range: crate::GpioAdcRange,
+) -> Option<u8> {
+    let offset_mv = gpio_adc_offset_mv(range);
+    if threshold_mv < offset_mv || threshold_mv - offset_mv > GPIO1_ADC_THRESHOLD_SPAN_MV {
+        return None;
+    }
+    Some(((threshold_mv - offset_mv + 4) / 8) as u8)
+}
+
+pub(crate) const fn gpio1_adc_threshold_mv_from_raw(raw: u8, range: crate::GpioAdcRange) -> u16 {
+    raw as u16 * 8 + gpio_adc_offset_mv(range)
+}
+
+pub(crate) fn battery_power_from_adc(adc_val: u32) -> Microwatts {
+    // 0.55uW per LSB; the 24-bit code times 11 still fits in a u32.
+    Microwatts((adc_val * 11 + 10) / 20)
+}
+
+pub(crate) fn battery_voltage_from_adc(adc_val: u16) -> Millivolts {
+    scale_tenths_mv(adc_val, 11)
+}
+
+pub(crate) fn battery_current_from_adc(adc_val: u16) -> Microamps {
+    Microamps(adc_val as i32 * 500)
+}
+
+pub(crate) fn aps_voltage_from_adc(adc_val: u16) -> Millivolts {
+    scale_tenths_mv(adc_val, 14)
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn acin_voltage_mv_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 1.7
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn acin_current_ma_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 0.625
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn vbus_voltage_mv_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 1.7
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn vbus_current_ma_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 0.375
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn internal_temperature_c_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 0.1 - 144.7
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn ts_pin_voltage_mv_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 0.8
+}
+
+/// GPIO channels have a 0.5mV LSB on top of a 0V or 0.7V range offset (REG85H).
+#[cfg(feature = "float")]
+pub(crate) fn gpio_voltage_mv_from_adc(adc_val: u16, range: crate::GpioAdcRange) -> f32 {
+    let offset_mv = match range {
+        crate::GpioAdcRange::Range00To20475V => 0.0,
+        crate::GpioAdcRange::Range07To27475V => 700.0,
+    };
+    adc_val as f32 * 0.5 + offset_mv
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn battery_power_uw_from_adc(adc_val: u32) -> f32 {
+    adc_val as f32 * 0.55
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn battery_voltage_mv_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 1.1
+}
+
+/// Battery charge and discharge current channels share a 0.5mA LSB.
+#[cfg(feature = "float")]
+pub(crate) fn battery_current_ma_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 0.5
+}
+
+#[cfg(feature = "float")]
+pub(crate) fn aps_voltage_mv_from_adc(adc_val: u16) -> f32 {
+    adc_val as f32 * 1.4
+}
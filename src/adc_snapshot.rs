@@ -0,0 +1,220 @@
+//! All ADC channels captured in a single burst read.
+//!
+//! The ADC result registers occupy the contiguous block REG56H-REG7FH, so
+//! [`AdcSnapshot`] is filled from one 42-byte read plus one read of
+//! `AdcEnable1`/`AdcEnable2`/`AdcSampleRateTsPinControl`/`GpioAdcInputRangeSetting`
+//! (REG82H-REG85H). Channels whose ADC is disabled read as `None`.
+
+use crate::adc_helpers::*;
+use crate::{DeciCelsius, GpioAdcId, GpioAdcRange, Microamps, Microwatts, Millivolts, field_sets};
+
+/// First register of the ADC result block (`AcinVoltageAdc`).
+pub(crate) const ADC_BLOCK_START: u8 = 0x56;
+/// Length of the ADC result block, REG56H through REG7FH.
+pub(crate) const ADC_BLOCK_LEN: usize = 0x80 - ADC_BLOCK_START as usize;
+/// First register of the ADC control block (`AdcEnable1`).
+pub(crate) const ADC_CONTROL_START: u8 = 0x82;
+/// Length of the ADC control block, REG82H through REG85H.
+pub(crate) const ADC_CONTROL_LEN: usize = 4;
+
+/// Raw ADC codes of every channel, `None` where the channel is disabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AdcSnapshot {
+    acin_voltage: Option<u16>,
+    acin_current: Option<u16>,
+    vbus_voltage: Option<u16>,
+    vbus_current: Option<u16>,
+    internal_temperature: Option<u16>,
+    ts_pin: Option<u16>,
+    gpio: [Option<(u16, GpioAdcRange)>; 4],
+    battery_power: Option<u32>,
+    battery_voltage: Option<u16>,
+    battery_charge_current: Option<u16>,
+    battery_discharge_current: Option<u16>,
+    aps_voltage: Option<u16>,
+}
+
+impl AdcSnapshot {
+    /// Decodes the REG56H-REG7FH block using the REG82H-REG85H control block.
+    pub(crate) fn from_registers(
+        block: &[u8; ADC_BLOCK_LEN],
+        control: &[u8; ADC_CONTROL_LEN],
+    ) -> Self {
+        let enable_1 = field_sets::AdcEnable1::from([control[0]]);
+        let enable_2 = field_sets::AdcEnable2::from([control[1]]);
+        let ranges = field_sets::GpioAdcInputRangeSetting::from([control[3]]);
+
+        let u16_at = |address: u8| {
+            let offset = (address - ADC_BLOCK_START) as usize;
+            u16::from_be_bytes([block[offset], block[offset + 1]])
+        };
+        let adc_12bit = |address: u8| adc_12bit_from_raw_u16(u16_at(address));
+        let adc_13bit = |address: u8| adc_13bit_from_raw_u16(u16_at(address));
+        let power_offset = (0x70 - ADC_BLOCK_START) as usize;
+        let power_raw = u32::from_be_bytes([
+            0,
+            block[power_offset],
+            block[power_offset + 1],
+            block[power_offset + 2],
+        ]);
+
+        let battery_voltage_enabled = enable_1.battery_voltage_adc_enable();
+        let battery_current_enabled = enable_1.battery_current_adc_enable();
+        let gpio_enabled = [
+            enable_2.gpio_0_adc_enable(),
+            enable_2.gpio_1_adc_enable(),
+            enable_2.gpio_2_adc_enable(),
+            enable_2.gpio_3_adc_enable(),
+        ];
+        let gpio_ranges = [
+            ranges.gpio_0_adc_input_range(),
+            ranges.gpio_1_adc_input_range(),
+            ranges.gpio_2_adc_input_range(),
+            ranges.gpio_3_adc_input_range(),
+        ];
+        let mut gpio = [None; 4];
+        for (index, channel) in gpio.iter_mut().enumerate() {
+            if gpio_enabled[index] {
+                let address = 0x64 + 2 * index as u8;
+                *channel = Some((adc_12bit(address), gpio_ranges[index]));
+            }
+        }
+
+        Self {
+            acin_voltage: enable_1.acin_voltage_adc_enable().then(|| adc_12bit(0x56)),
+            acin_current: enable_1.acin_current_adc_enable().then(|| adc_12bit(0x58)),
+            vbus_voltage: enable_1.vbus_voltage_adc_enable().then(|| adc_12bit(0x5A)),
+            vbus_current: enable_1.vbus_current_adc_enable().then(|| adc_12bit(0x5C)),
+            internal_temperature: enable_2
+                .internal_temperature_adc_enable()
+                .then(|| adc_12bit(0x5E)),
+            ts_pin: enable_1.ts_pin_adc_enable().then(|| adc_12bit(0x62)),
+            gpio,
+            // The power channel is the product of battery voltage and current.
+            battery_power: (battery_voltage_enabled && battery_current_enabled)
+                .then(|| adc_24bit_from_raw_u32(power_raw)),
+            battery_voltage: battery_voltage_enabled.then(|| adc_12bit(0x78)),
+            battery_charge_current: battery_current_enabled.then(|| adc_13bit(0x7A)),
+            battery_discharge_current: battery_current_enabled.then(|| adc_13bit(0x7C)),
+            aps_voltage: enable_1.aps_voltage_adc_enable().then(|| adc_12bit(0x7E)),
+        }
+    }
+
+    pub fn acin_voltage(&self) -> Option<Millivolts> {
+        self.acin_voltage.map(acin_voltage_from_adc)
+    }
+
+    pub fn acin_current(&self) -> Option<Microamps> {
+        self.acin_current.map(acin_current_from_adc)
+    }
+
+    pub fn vbus_voltage(&self) -> Option<Millivolts> {
+        self.vbus_voltage.map(vbus_voltage_from_adc)
+    }
+
+    pub fn vbus_current(&self) -> Option<Microamps> {
+        self.vbus_current.map(vbus_current_from_adc)
+    }
+
+    pub fn internal_temperature(&self) -> Option<DeciCelsius> {
+        self.internal_temperature.map(internal_temperature_from_adc)
+    }
+
+    pub fn ts_pin_voltage(&self) -> Option<Millivolts> {
+        self.ts_pin.map(ts_pin_voltage_from_adc)
+    }
+
+    pub fn gpio_voltage(&self, gpio: GpioAdcId) -> Option<Millivolts> {
+        self.gpio[gpio as usize].map(|(adc_val, range)| gpio_voltage_from_adc(adc_val, range))
+    }
+
+    pub fn battery_instantaneous_power(&self) -> Option<Microwatts> {
+        self.battery_power.map(battery_power_from_adc)
+    }
+
+    pub fn battery_voltage(&self) -> Option<Millivolts> {
+        self.battery_voltage.map(battery_voltage_from_adc)
+    }
+
+    pub fn battery_charge_current(&self) -> Option<Microamps> {
+        self.battery_charge_current.map(battery_current_from_adc)
+    }
+
+    pub fn battery_discharge_current(&self) -> Option<Microamps> {
+        self.battery_discharge_current.map(battery_current_from_adc)
+    }
+
+    /// Net battery current, positive while charging and negative while discharging.
+    pub fn battery_current(&self) -> Option<Microamps> {
+        let charge = self.battery_charge_current()?;
+        let discharge = self.battery_discharge_current()?;
+        Some(Microamps(charge.ua() - discharge.ua()))
+    }
+
+    pub fn aps_voltage(&self) -> Option<Millivolts> {
+        self.aps_voltage.map(aps_voltage_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn acin_voltage_mv(&self) -> Option<f32> {
+        self.acin_voltage.map(acin_voltage_mv_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn acin_current_ma(&self) -> Option<f32> {
+        self.acin_current.map(acin_current_ma_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn vbus_voltage_mv(&self) -> Option<f32> {
+        self.vbus_voltage.map(vbus_voltage_mv_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn vbus_current_ma(&self) -> Option<f32> {
+        self.vbus_current.map(vbus_current_ma_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn internal_temperature_c(&self) -> Option<f32> {
+        self.internal_temperature
+            .map(internal_temperature_c_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn ts_pin_voltage_mv(&self) -> Option<f32> {
+        self.ts_pin.map(ts_pin_voltage_mv_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn gpio_voltage_mv(&self, gpio: GpioAdcId) -> Option<f32> {
+        self.gpio[gpio as usize].map(|(adc_val, range)| gpio_voltage_mv_from_adc(adc_val, range))
+    }
+
+    #[cfg(feature = "float")]
+    pub fn battery_instantaneous_power_uw(&self) -> Option<f32> {
+        self.battery_power.map(battery_power_uw_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn battery_voltage_mv(&self) -> Option<f32> {
+        self.battery_voltage.map(battery_voltage_mv_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn battery_charge_current_ma(&self) -> Option<f32> {
+        self.battery_charge_current.map(battery_current_ma_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn battery_discharge_current_ma(&self) -> Option<f32> {
+        self.battery_discharge_current
+            .map(battery_current_ma_from_adc)
+    }
+
+    #[cfg(feature = "float")]
+    pub fn aps_voltage_mv(&self) -> Option<f32> {
+        self.aps_voltage.map(aps_voltage_mv_from_adc)
+    }
+}
@@ -0,0 +1,130 @@
+//! Data kept in `DataBuffer0`-`DataBuffer5` (REG06H-REG0BH).
+//!
+//! The six buffer registers keep their contents while any power source is
+//! present, including across `Axp192::power_off`, and return to
+//! [`BACKUP_DATA_RESET`] only when every source is removed.
+//! `Axp192::read_backup_data` and `Axp192::write_backup_data` move all six bytes
+//! in one I2C transfer.
+//!
+//! A [`BackupRecord`] is a small struct stored in the buffers as a version byte,
+//! a [`BACKUP_PAYLOAD_LEN`]-byte payload and a CRC-8 over both, so a reader can
+//! tell a record apart from reset contents, corrupted bytes and a record of an
+//! older format (see [`BackupError`]).
+
+use crate::crc::crc8;
+
+/// Address of `DataBuffer0`; `DataBuffer1`-`DataBuffer5` follow it.
+pub(crate) const DATA_BUFFER_START: u8 = 0x06;
+
+/// Number of `DataBuffer` registers.
+pub const BACKUP_DATA_LEN: usize = 6;
+
+/// Bytes of a [`BackupRecord`] payload: the buffers minus version and CRC.
+pub const BACKUP_PAYLOAD_LEN: usize = BACKUP_DATA_LEN - 2;
+
+/// Contents of the buffers after every power source was removed, from the
+/// register reset values in `device.yaml`.
+pub const BACKUP_DATA_RESET: [u8; BACKUP_DATA_LEN] = [0xF0, 0x0F, 0x00, 0xFF, 0x00, 0x00];
+
+/// Why the buffers do not hold a valid record.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum BackupError {
+    /// The buffers hold their reset values: nothing was stored since all
+    /// power sources were removed.
+    #[error("Backup data is uninitialized")]
+    Uninitialized,
+    #[error("Backup data checksum mismatch")]
+    ChecksumMismatch,
+    /// A valid record of another format version.
+    #[error("Unsupported backup record version {0}")]
+    UnsupportedVersion(u8),
+}
+
+/// A struct stored in the `DataBuffer` registers.
+pub trait BackupRecord: Sized {
+    /// Format version written with the payload. Change it whenever the
+    /// payload layout changes.
+    const VERSION: u8;
+
+    fn to_payload(&self) -> [u8; BACKUP_PAYLOAD_LEN];
+
+    fn from_payload(payload: &[u8; BACKUP_PAYLOAD_LEN]) -> Self;
+}
+
+/// Serializes `record` as version, payload and CRC-8.
+pub fn encode_record<T: BackupRecord>(record: &T) -> [u8; BACKUP_DATA_LEN] {
+    let mut data = [0u8; BACKUP_DATA_LEN];
+    data[0] = T::VERSION;
+    data[1..=BACKUP_PAYLOAD_LEN].copy_from_slice(&record.to_payload());
+    data[BACKUP_DATA_LEN - 1] = crc8(&data[..BACKUP_DATA_LEN - 1]);
+    data
+}
+
+/// Loads a record written by [`encode_record`].
+pub fn decode_record<T: BackupRecord>(data: &[u8; BACKUP_DATA_LEN]) -> Result<T, BackupError> {
+    if *data == BACKUP_DATA_RESET {
+        return Err(BackupError::Uninitialized);
+    }
+    let (body, crc) = data.split_at(BACKUP_DATA_LEN - 1);
+    if crc8(body) != crc[0] {
+        return Err(BackupError::ChecksumMismatch);
+    }
+    if body[0] != T::VERSION {
+        return Err(BackupError::UnsupportedVersion(body[0]));
+    }
+    let mut payload = [0u8; BACKUP_PAYLOAD_LEN];
+    payload.copy_from_slice(&body[1..]);
+    Ok(T::from_payload(&payload))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    struct BootCount(u32);
+
+    impl BackupRecord for BootCount {
+        const VERSION: u8 = 0;
+
+        fn to_payload(&self) -> [u8; BACKUP_PAYLOAD_LEN] {
+            self.0.to_le_bytes()
+        }
+
+        fn from_payload(payload: &[u8; BACKUP_PAYLOAD_LEN]) -> Self {
+            Self(u32::from_le_bytes(*payload))
+        }
+    }
+
+    #[test]
+    fn records_round_trip() {
+        let data = encode_record(&BootCount(7));
+        ::core::assert_eq!(decode_record(&data), Ok(BootCount(7)));
+    }
+
+    #[test]
+    fn decoding_tells_invalid_contents_apart() {
+        ::core::assert_eq!(
+            decode_record::<BootCount>(&BACKUP_DATA_RESET),
+            Err(BackupError::Uninitialized)
+        );
+        // A record of version 0 with an all-zero payload still has a checksum.
+        ::core::assert_eq!(
+            decode_record::<BootCount>(&[0; BACKUP_DATA_LEN]),
+            Err(BackupError::ChecksumMismatch)
+        );
+        let mut data = encode_record(&BootCount(7));
+        data[1] ^= 0x01;
+        ::core::assert_eq!(
+            decode_record::<BootCount>(&data),
+            Err(BackupError::ChecksumMismatch)
+        );
+        let mut data = [3, 0, 0, 0, 0, 0];
+        data[BACKUP_DATA_LEN - 1] = crc8(&data[..BACKUP_DATA_LEN - 1]);
+        ::core::assert_eq!(
+            decode_record::<BootCount>(&data),
+            Err(BackupError::UnsupportedVersion(3))
+        );
+    }
+}
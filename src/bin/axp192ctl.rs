@@ -0,0 +1,52 @@
+//! `axp192ctl`: inspects and configures an AXP192 over Linux i2c-dev.
+//!
+//! The commands are implemented in `axp192_dd::cli`; this binary only opens
+//! the bus and repeats `watch` samples.
+
+use std::process::ExitCode;
+use std::time::Duration;
+
+use axp192_dd::cli::{self, CliError, Command};
+use axp192_dd::{Axp192, AxpError};
+use linux_embedded_hal::I2cdev;
+
+fn main() -> ExitCode {
+    let args = match cli::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("axp192ctl: {error}\n\n{}", cli::USAGE);
+            return ExitCode::from(2);
+        }
+    };
+    if args.command == Command::Help {
+        print!("{}", cli::USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    let i2c = match I2cdev::new(&args.bus) {
+        Ok(i2c) => i2c,
+        Err(error) => {
+            eprintln!("axp192ctl: cannot open {}: {error}", args.bus);
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut axp = Axp192::new(i2c);
+    let mut stdout = std::io::stdout().lock();
+    loop {
+        match cli::execute(&args.command, &mut axp, &mut stdout) {
+            Ok(()) => {}
+            Err(CliError::Axp(AxpError::I2c(error))) => {
+                eprintln!("axp192ctl: I2C error on {}: {error:?}", args.bus);
+                return ExitCode::FAILURE;
+            }
+            Err(error) => {
+                eprintln!("axp192ctl: {error}");
+                return ExitCode::FAILURE;
+            }
+        }
+        let Command::Watch { interval_ms } = args.command else {
+            return ExitCode::SUCCESS;
+        };
+        std::thread::sleep(Duration::from_millis(interval_ms.into()));
+    }
+}
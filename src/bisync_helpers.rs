@@ -79,3 +79,45 @@ where
 {
     op.modify_async(f).await
 }
+
+#[allow(dead_code)]
+#[only_sync]
+fn modify_if_changed_internal<'a, Interface, Register, Access>(
+    op: &mut device_driver::RegisterOperation<'a, Interface, u8, Register, Access>,
+    f: impl FnOnce(&mut Register),
+) -> Result<bool, Interface::Error>
+where
+    Interface: RegisterInterface<AddressType = u8>,
+    Register: device_driver::FieldSet + Copy + PartialEq,
+    Access: device_driver::ReadCapability + device_driver::WriteCapability,
+{
+    let current = op.read()?;
+    let mut desired = current;
+    f(&mut desired);
+    if desired == current {
+        return Ok(false);
+    }
+    op.write(|r| *r = desired)?;
+    Ok(true)
+}
+
+#[allow(dead_code)]
+#[only_async]
+async fn modify_if_changed_internal<'a, Interface, Register, Access>(
+    op: &mut device_driver::RegisterOperation<'a, Interface, u8, Register, Access>,
+    f: impl FnOnce(&mut Register),
+) -> Result<bool, Interface::Error>
+where
+    Interface: RegisterInterface<AddressType = u8>,
+    Register: device_driver::FieldSet + Copy + PartialEq,
+    Access: device_driver::ReadCapability + device_driver::WriteCapability,
+{
+    let current = op.read_async().await?;
+    let mut desired = current;
+    f(&mut desired);
+    if desired == current {
+        return Ok(false);
+    }
+    op.write_async(|r| *r = desired).await?;
+    Ok(true)
+}
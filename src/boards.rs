@@ -0,0 +1,144 @@
+//! Power profiles of M5Stack devices built around the AXP192.
+//!
+//! The rail assignments follow the M5Stack schematics and the power-up
+//! sequence of their Arduino libraries. `Axp192::init_for_board` applies a
+//! profile, and the board helpers (`set_backlight_percent`, `set_vibration`,
+//! `set_speaker_enable`, `set_power_led`) drive the right rail or GPIO for it.
+//!
+//! | Output | M5StickC / PLUS | Core2 | Tough |
+//! |--------|-----------------|-------|-------|
+//! | DCDC1  | ESP32, 3.3V | ESP32, 3.35V | ESP32, 3.35V |
+//! | DCDC3  | unused | LCD backlight | LCD backlight |
+//! | LDO2   | LCD backlight | LCD logic and SD card, 3.3V | LCD logic and SD card, 3.3V |
+//! | LDO3   | LCD logic, 3.0V | vibration motor, 2.0V | unused |
+//! | GPIO0  | LDO for the microphone, 3.3V | LDO enabling the 5V bus boost | LDO enabling the 5V bus boost |
+//! | GPIO1  | - | green power LED, active low | - |
+//! | GPIO2  | - | speaker amplifier enable | speaker amplifier enable |
+//! | GPIO4  | - | LCD reset, active low | LCD reset, active low |
+//! | EXTEN  | 5V output on the HAT/Grove port | 5V bus boost | 5V bus boost |
+
+use crate::{
+    ChargerConfig, DcDcModeSelection, DcId, LdoId, PekConfig, PekLongPressTime, PekPowerOnTime,
+    PekShutdownTime, PowerConfig, PwrokSignalDelay, RailConfig,
+};
+
+/// An M5Stack device with a known AXP192 wiring.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Board {
+    M5StickC,
+    M5StickCPlus,
+    /// M5Stack Core2, including Core2 for AWS.
+    Core2,
+    Tough,
+}
+
+/// A regulator output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Rail {
+    Dcdc(DcId),
+    Ldo(LdoId),
+}
+
+/// Lowest backlight supply voltage; the LCD is dark below this.
+pub(crate) const BACKLIGHT_MIN_MV: u16 = 2500;
+/// Highest backlight supply voltage.
+pub(crate) const BACKLIGHT_MAX_MV: u16 = 3300;
+
+const M5STICKC_POWER: PowerConfig = PowerConfig {
+    dcdc1: RailConfig::on(3300),
+    dcdc2: RailConfig::off(1250),
+    dcdc3: RailConfig::off(2500),
+    ldo2: RailConfig::on(3000),
+    ldo3: RailConfig::on(3000),
+    gpio0_ldo: Some(RailConfig::on(3300)),
+    exten: true,
+    dcdc1_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc2_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc3_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc_frequency_khz: 1500,
+    charger: Some(ChargerConfig {
+        current_ma: 100,
+        ..ChargerConfig::DEFAULT
+    }),
+    pek: Some(PekConfig {
+        power_on_time: PekPowerOnTime::Ms128,
+        long_press_time: PekLongPressTime::S10,
+        shutdown_time: PekShutdownTime::S4,
+        auto_shutdown: true,
+        pwrok_delay: PwrokSignalDelay::Ms64,
+    }),
+};
+
+const CORE2_POWER: PowerConfig = PowerConfig {
+    dcdc1: RailConfig::on(3350),
+    dcdc2: RailConfig::off(1250),
+    dcdc3: RailConfig::on(2800),
+    ldo2: RailConfig::on(3300),
+    ldo3: RailConfig::off(2000),
+    gpio0_ldo: Some(RailConfig::on(3300)),
+    exten: true,
+    dcdc1_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc2_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc3_mode: DcDcModeSelection::AutoPfmPwm,
+    dcdc_frequency_khz: 1500,
+    charger: Some(ChargerConfig {
+        current_ma: 100,
+        ..ChargerConfig::DEFAULT
+    }),
+    pek: Some(PekConfig {
+        power_on_time: PekPowerOnTime::Ms512,
+        long_press_time: PekLongPressTime::S10,
+        shutdown_time: PekShutdownTime::S4,
+        auto_shutdown: true,
+        pwrok_delay: PwrokSignalDelay::Ms64,
+    }),
+};
+
+const TOUGH_POWER: PowerConfig = PowerConfig {
+    ldo3: RailConfig::off(3300),
+    ..CORE2_POWER
+};
+
+impl Board {
+    /// The regulator, charger and power key setup applied by `init_for_board`.
+    pub const fn power_config(self) -> PowerConfig {
+        match self {
+            Board::M5StickC | Board::M5StickCPlus => M5STICKC_POWER,
+            Board::Core2 => CORE2_POWER,
+            Board::Tough => TOUGH_POWER,
+        }
+    }
+
+    /// The rail feeding the LCD backlight; its voltage sets the brightness.
+    pub const fn backlight_rail(self) -> Rail {
+        match self {
+            Board::M5StickC | Board::M5StickCPlus => Rail::Ldo(LdoId::Ldo2),
+            Board::Core2 | Board::Tough => Rail::Dcdc(DcId::Dcdc3),
+        }
+    }
+
+    /// The rail driving the vibration motor, if the board has one.
+    pub const fn vibration_rail(self) -> Option<Rail> {
+        match self {
+            Board::Core2 => Some(Rail::Ldo(LdoId::Ldo3)),
+            _ => None,
+        }
+    }
+
+    /// Whether the speaker amplifier is enabled through AXP192 GPIO2.
+    pub const fn has_speaker_enable(self) -> bool {
+        matches!(self, Board::Core2 | Board::Tough)
+    }
+
+    /// Whether a power LED is wired to AXP192 GPIO1.
+    pub const fn has_power_led(self) -> bool {
+        matches!(self, Board::Core2)
+    }
+
+    /// Whether the LCD reset line is wired to AXP192 GPIO4.
+    pub const fn has_lcd_reset(self) -> bool {
+        matches!(self, Board::Core2 | Board::Tough)
+    }
+}
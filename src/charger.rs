@@ -0,0 +1,303 @@
+//! Battery charger settings in physical units.
+//!
+//! `ChargeControl1` (REG33H) and `ChargeControl2` (REG34H) hold the charger
+//! configuration as enum-coded fields. [`ChargerConfig`] presents them as
+//! millivolts and milliamps, with the remaining discrete settings kept as their
+//! register enums.
+//!
+//! [`ChargerState`] decodes `PowerStatus` (REG00H) and `ChargeStatus` (REG01H)
+//! into the phase the charger is in.
+
+use crate::field_sets::{ChargeStatus, PowerStatus};
+use crate::{
+    AxpError, BatteryFlowDirection, ChargeCurrentValue, ChargeEndCurrentThresholdValue,
+    ChargeTargetVoltageValue, ConstantCurrentTimeoutValue, ExternalPathChargeCurrentValue,
+    PrechargeTimeoutValue,
+};
+
+impl ChargeCurrentValue {
+    const ALL: [ChargeCurrentValue; 16] = [
+        ChargeCurrentValue::Ma100,
+        ChargeCurrentValue::Ma190,
+        ChargeCurrentValue::Ma280,
+        ChargeCurrentValue::Ma360,
+        ChargeCurrentValue::Ma450,
+        ChargeCurrentValue::Ma550,
+        ChargeCurrentValue::Ma630,
+        ChargeCurrentValue::Ma700,
+        ChargeCurrentValue::Ma780,
+        ChargeCurrentValue::Ma880,
+        ChargeCurrentValue::Ma960,
+        ChargeCurrentValue::Ma1000,
+        ChargeCurrentValue::Ma1080,
+        ChargeCurrentValue::Ma1160,
+        ChargeCurrentValue::Ma1240,
+        ChargeCurrentValue::Ma1320,
+    ];
+
+    /// The charge current in mA.
+    pub const fn ma(self) -> u16 {
+        match self {
+            ChargeCurrentValue::Ma100 => 100,
+            ChargeCurrentValue::Ma190 => 190,
+            ChargeCurrentValue::Ma280 => 280,
+            ChargeCurrentValue::Ma360 => 360,
+            ChargeCurrentValue::Ma450 => 450,
+            ChargeCurrentValue::Ma550 => 550,
+            ChargeCurrentValue::Ma630 => 630,
+            ChargeCurrentValue::Ma700 => 700,
+            ChargeCurrentValue::Ma780 => 780,
+            ChargeCurrentValue::Ma880 => 880,
+            ChargeCurrentValue::Ma960 => 960,
+            ChargeCurrentValue::Ma1000 => 1000,
+            ChargeCurrentValue::Ma1080 => 1080,
+            ChargeCurrentValue::Ma1160 => 1160,
+            ChargeCurrentValue::Ma1240 => 1240,
+            ChargeCurrentValue::Ma1320 => 1320,
+        }
+    }
+
+    /// The highest setting not exceeding `current_ma`, or `None` outside 100-1320mA.
+    pub fn from_ma(current_ma: u16) -> Option<Self> {
+        if current_ma > ChargeCurrentValue::Ma1320.ma() {
+            return None;
+        }
+        Self::ALL
+            .iter()
+            .rev()
+            .find(|value| value.ma() <= current_ma)
+            .copied()
+    }
+}
+
+impl ExternalPathChargeCurrentValue {
+    const ALL: [ExternalPathChargeCurrentValue; 8] = [
+        ExternalPathChargeCurrentValue::Ma300,
+        ExternalPathChargeCurrentValue::Ma400,
+        ExternalPathChargeCurrentValue::Ma500,
+        ExternalPathChargeCurrentValue::Ma600,
+        ExternalPathChargeCurrentValue::Ma700,
+        ExternalPathChargeCurrentValue::Ma800,
+        ExternalPathChargeCurrentValue::Ma900,
+        ExternalPathChargeCurrentValue::Ma1000,
+    ];
+
+    /// The external path charge current in mA.
+    pub const fn ma(self) -> u16 {
+        300 + self as u16 * 100
+    }
+
+    /// The highest setting not exceeding `current_ma`, or `None` outside 300-1000mA.
+    pub fn from_ma(current_ma: u16) -> Option<Self> {
+        if !(300..=1000).contains(&current_ma) {
+            return None;
+        }
+        Some(Self::ALL[((current_ma - 300) / 100) as usize])
+    }
+}
+
+impl ChargeTargetVoltageValue {
+    /// The charge target voltage in mV.
+    pub const fn mv(self) -> u16 {
+        match self {
+            ChargeTargetVoltageValue::V410 => 4100,
+            ChargeTargetVoltageValue::V415 => 4150,
+            ChargeTargetVoltageValue::V420 => 4200,
+            ChargeTargetVoltageValue::V436 => 4360,
+        }
+    }
+
+    /// The setting for exactly `voltage_mv`, which must be 4100, 4150, 4200 or 4360.
+    pub const fn from_mv(voltage_mv: u16) -> Option<Self> {
+        match voltage_mv {
+            4100 => Some(ChargeTargetVoltageValue::V410),
+            4150 => Some(ChargeTargetVoltageValue::V415),
+            4200 => Some(ChargeTargetVoltageValue::V420),
+            4360 => Some(ChargeTargetVoltageValue::V436),
+            _ => None,
+        }
+    }
+}
+
+impl ChargeEndCurrentThresholdValue {
+    /// Termination threshold as a percentage of the charge current.
+    pub const fn percent(self) -> u8 {
+        match self {
+            ChargeEndCurrentThresholdValue::Percent10 => 10,
+            ChargeEndCurrentThresholdValue::Percent15 => 15,
+        }
+    }
+
+    /// The setting for exactly `percent`, which must be 10 or 15.
+    pub const fn from_percent(percent: u8) -> Option<Self> {
+        match percent {
+            10 => Some(ChargeEndCurrentThresholdValue::Percent10),
+            15 => Some(ChargeEndCurrentThresholdValue::Percent15),
+            _ => None,
+        }
+    }
+}
+
+impl PrechargeTimeoutValue {
+    pub const fn minutes(self) -> u8 {
+        30 + self as u8 * 10
+    }
+}
+
+impl ConstantCurrentTimeoutValue {
+    pub const fn hours(self) -> u8 {
+        7 + self as u8
+    }
+}
+
+/// Charger configuration of `ChargeControl1` and `ChargeControl2`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargerConfig {
+    pub enabled: bool,
+    /// Charge target voltage: 4100, 4150, 4200 or 4360mV.
+    pub target_voltage_mv: u16,
+    /// Internal path charge current, 100-1320mA. Rounded down to the next step.
+    pub current_ma: u16,
+    pub termination: ChargeEndCurrentThresholdValue,
+    pub precharge_timeout: PrechargeTimeoutValue,
+    pub cc_timeout: ConstantCurrentTimeoutValue,
+    pub external_path_enabled: bool,
+    /// External path charge current, 300-1000mA in 100mA steps. Rounded down.
+    pub external_path_current_ma: u16,
+}
+
+impl ChargerConfig {
+    /// The register reset values listed in `device.yaml`.
+    pub const DEFAULT: ChargerConfig = ChargerConfig {
+        enabled: true,
+        target_voltage_mv: 4200,
+        current_ma: 780,
+        termination: ChargeEndCurrentThresholdValue::Percent10,
+        precharge_timeout: PrechargeTimeoutValue::Min40,
+        cc_timeout: ConstantCurrentTimeoutValue::Hours8,
+        external_path_enabled: false,
+        external_path_current_ma: 300,
+    };
+
+    /// Checks every value and converts it to its register enum.
+    pub(crate) fn encode<E>(&self) -> Result<RawChargerConfig, AxpError<E>> {
+        Ok(RawChargerConfig {
+            target_voltage: ChargeTargetVoltageValue::from_mv(self.target_voltage_mv)
+                .ok_or(AxpError::InvalidVoltage(self.target_voltage_mv))?,
+            current: ChargeCurrentValue::from_ma(self.current_ma)
+                .ok_or(AxpError::InvalidCurrent(self.current_ma))?,
+            external_path_current: ExternalPathChargeCurrentValue::from_ma(
+                self.external_path_current_ma,
+            )
+            .ok_or(AxpError::InvalidCurrent(self.external_path_current_ma))?,
+        })
+    }
+}
+
+impl Default for ChargerConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Register enums of the physical values of a validated [`ChargerConfig`].
+pub(crate) struct RawChargerConfig {
+    pub target_voltage: ChargeTargetVoltageValue,
+    pub current: ChargeCurrentValue,
+    pub external_path_current: ExternalPathChargeCurrentValue,
+}
+
+/// What the charger is doing, decoded by `Axp192::charger_state`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargerState {
+    /// No battery is detected.
+    NoBattery,
+    /// The battery supplies the system: there is no usable ACIN or VBUS, or the
+    /// load takes more than the input current limit leaves for charging.
+    Discharging,
+    /// A deeply discharged battery is being pre-charged (activation mode).
+    PreCharge,
+    /// Charging at the configured current.
+    ConstantCurrent,
+    /// Charging below the configured current, normally because the battery
+    /// has reached the target voltage and the current is tapering. This is
+    /// inferred from `ChargeStatus` reporting less current than expected, which
+    /// an input current limit (e.g. 500mA on VBUS) leaving too little for the
+    /// charger causes as well.
+    ConstantVoltage,
+    /// External power is usable and the charger is enabled, but not charging:
+    /// the battery is full. A charge that stopped on a safety timeout reads
+    /// the same, as no status bit tells the two apart.
+    Done,
+    /// External power is usable but the charger is disabled in `ChargeControl1`.
+    Disabled,
+    /// Charging is suspended.
+    Fault(ChargerFault),
+}
+
+/// Why charging is suspended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ChargerFault {
+    /// The AXP192 die is over temperature.
+    DieOverTemperature,
+    /// The TS pin voltage is outside the charge temperature window
+    /// (`BatteryChargeLowTempThreshold`/`BatteryChargeHighTempThreshold`).
+    BatteryTemperature,
+}
+
+impl ChargerState {
+    /// Decodes the status registers.
+    ///
+    /// `charge_enabled` is the `ChargeControl1` enable bit, and
+    /// `battery_temperature_fault` is set when the TS pin voltage is outside
+    /// the charge temperature window.
+    pub fn decode(
+        power: PowerStatus,
+        charge: ChargeStatus,
+        charge_enabled: bool,
+        battery_temperature_fault: bool,
+    ) -> Self {
+        if !charge.battery_connected() {
+            return ChargerState::NoBattery;
+        }
+        if charge.axp_192_over_temperature() {
+            return ChargerState::Fault(ChargerFault::DieOverTemperature);
+        }
+        if !power.acin_usable() && !power.vbus_usable() {
+            return ChargerState::Discharging;
+        }
+        if charge.charging_in_progress() {
+            // The charger stays active while the load draws the battery down.
+            if power.battery_flow() == BatteryFlowDirection::Discharging {
+                return ChargerState::Discharging;
+            }
+            if charge.battery_in_activation_mode() {
+                return ChargerState::PreCharge;
+            }
+            // Usually the CV phase tapering, but an input current limit
+            // reads the same.
+            if charge.charge_current_less_than_expected() {
+                return ChargerState::ConstantVoltage;
+            }
+            return ChargerState::ConstantCurrent;
+        }
+        if battery_temperature_fault {
+            return ChargerState::Fault(ChargerFault::BatteryTemperature);
+        }
+        if !charge_enabled {
+            return ChargerState::Disabled;
+        }
+        ChargerState::Done
+    }
+
+    /// Whether current is flowing into the battery.
+    pub const fn is_charging(self) -> bool {
+        matches!(
+            self,
+            ChargerState::PreCharge | ChargerState::ConstantCurrent | ChargerState::ConstantVoltage
+        )
+    }
+}
@@ -0,0 +1,814 @@
+//! Argument parsing and output of the `axp192ctl` command-line tool.
+//!
+//! The tool itself only opens a Linux i2c-dev bus (`linux` feature); everything
+//! else lives here and works on any blocking [`Axp192`], so the commands can be
+//! run on a host against [`sim::SimAxp192`](crate::sim::SimAxp192) or any other
+//! mocked bus. Registers are addressed by their `device.yaml` names, as listed
+//! in [`register_map`], and printed with one line per field of
+//! [`register_map::fields`].
+//!
+//! The commands are listed in [`USAGE`].
+
+use std::io::Write;
+
+use device_driver::RegisterInterface;
+
+use crate::config;
+use crate::register_map::{self, RegisterInfo};
+use crate::{
+    AdcSnapshot, Axp192, AxpError, ChargerFault, ChargerState, DcId, GpioAdcId, LdoId, Rail,
+    RailConfig,
+};
+
+/// Bus opened when `--bus` is not given.
+pub const DEFAULT_BUS: &str = "/dev/i2c-1";
+
+/// Default `watch` sampling interval.
+pub const DEFAULT_WATCH_INTERVAL_MS: u32 = 1000;
+
+/// Shortest `watch` sampling interval; every sample takes several I2C reads.
+pub const MIN_WATCH_INTERVAL_MS: u32 = 10;
+
+pub const USAGE: &str = "\
+usage: axp192ctl [--bus /dev/i2c-N] <command>
+
+commands:
+  status              power inputs, battery and regulator state
+  dump                every register with its name and value
+  get <register>      one register, by name or address (e.g. PowerStatus, 0x00)
+  set <rail> <mV>     output voltage of dcdc1-3, ldo2, ldo3 or gpio0
+  adc                 all ADC channels
+  irq [clear]         enabled and pending IRQs, optionally clearing the pending ones
+  watch [ms]          battery, input and IRQ state, sampled every ms (default 1000, min 10)
+";
+
+/// A regulator whose voltage `set` can program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoltageOutput {
+    Rail(Rail),
+    /// GPIO0 in low-noise LDO mode.
+    Gpio0Ldo,
+}
+
+impl VoltageOutput {
+    fn parse(name: &str) -> Option<Self> {
+        let output = match name.to_ascii_lowercase().as_str() {
+            "dcdc1" => VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc1)),
+            "dcdc2" => VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc2)),
+            "dcdc3" => VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc3)),
+            "ldo2" => VoltageOutput::Rail(Rail::Ldo(LdoId::Ldo2)),
+            "ldo3" => VoltageOutput::Rail(Rail::Ldo(LdoId::Ldo3)),
+            "gpio0" | "ldoio0" => VoltageOutput::Gpio0Ldo,
+            _ => return None,
+        };
+        Some(output)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    Status,
+    Dump,
+    Get(&'static RegisterInfo),
+    Set {
+        output: VoltageOutput,
+        voltage_mv: u16,
+    },
+    Adc,
+    Irq {
+        clear: bool,
+    },
+    /// Prints one sample per [`execute`] call; the caller repeats it every `interval_ms`.
+    Watch {
+        interval_ms: u32,
+    },
+    Help,
+}
+
+/// Parsed command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    /// Path of the i2c-dev bus.
+    pub bus: String,
+    pub command: Command,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ParseError {
+    #[error("missing command")]
+    MissingCommand,
+    #[error("unknown command `{0}`")]
+    UnknownCommand(String),
+    #[error("missing {0}")]
+    MissingArgument(&'static str),
+    #[error("unexpected argument `{0}`")]
+    UnexpectedArgument(String),
+    #[error("unknown register `{0}`")]
+    UnknownRegister(String),
+    #[error("unknown rail `{0}`, expected dcdc1-3, ldo2, ldo3 or gpio0")]
+    UnknownRail(String),
+    #[error("invalid number `{0}`")]
+    InvalidNumber(String),
+    #[error("watch interval `{0}` is below the {MIN_WATCH_INTERVAL_MS}ms minimum")]
+    IntervalTooShort(String),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CliError<E: core::fmt::Debug> {
+    #[error("{0}")]
+    Axp(AxpError<E>),
+    #[error("output error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl<E: core::fmt::Debug> From<AxpError<E>> for CliError<E> {
+    fn from(error: AxpError<E>) -> Self {
+        CliError::Axp(error)
+    }
+}
+
+/// Parses the arguments following the program name.
+pub fn parse<I, S>(args: I) -> Result<Args, ParseError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter().map(|arg| arg.as_ref().to_string());
+    let mut bus = String::from(DEFAULT_BUS);
+    let name = loop {
+        match args.next() {
+            Some(arg) if arg == "-b" || arg == "--bus" => {
+                bus = args.next().ok_or(ParseError::MissingArgument("bus path"))?;
+            }
+            Some(arg) => break arg,
+            None => return Err(ParseError::MissingCommand),
+        }
+    };
+
+    let command = match name.as_str() {
+        "status" => Command::Status,
+        "dump" => Command::Dump,
+        "get" => {
+            let register = args.next().ok_or(ParseError::MissingArgument("register"))?;
+            Command::Get(find_register(&register)?)
+        }
+        "set" => {
+            let rail = args.next().ok_or(ParseError::MissingArgument("rail"))?;
+            let output = VoltageOutput::parse(&rail).ok_or(ParseError::UnknownRail(rail))?;
+            let voltage = args.next().ok_or(ParseError::MissingArgument("voltage"))?;
+            let voltage_mv = voltage
+                .trim_end_matches("mV")
+                .trim_end_matches("mv")
+                .parse()
+                .map_err(|_| ParseError::InvalidNumber(voltage))?;
+            Command::Set { output, voltage_mv }
+        }
+        "adc" => Command::Adc,
+        "irq" => match args.next() {
+            None => Command::Irq { clear: false },
+            Some(arg) if arg == "clear" => Command::Irq { clear: true },
+            Some(arg) => return Err(ParseError::UnexpectedArgument(arg)),
+        },
+        "watch" => {
+            let interval_ms = match args.next() {
+                Some(interval) => match interval.parse() {
+                    Ok(interval_ms) if interval_ms >= MIN_WATCH_INTERVAL_MS => interval_ms,
+                    Ok(_) => return Err(ParseError::IntervalTooShort(interval)),
+                    Err(_) => return Err(ParseError::InvalidNumber(interval)),
+                },
+                None => DEFAULT_WATCH_INTERVAL_MS,
+            };
+            Command::Watch { interval_ms }
+        }
+        "help" | "-h" | "--help" => Command::Help,
+        _ => return Err(ParseError::UnknownCommand(name)),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(ParseError::UnexpectedArgument(extra));
+    }
+    Ok(Args { bus, command })
+}
+
+/// Looks a register up by name (case-insensitive) or by address in decimal,
+/// `0x`-prefixed or `h`-suffixed hex.
+fn find_register(register: &str) -> Result<&'static RegisterInfo, ParseError> {
+    let unknown = || ParseError::UnknownRegister(register.to_string());
+    if let Some(info) = register_map::by_name(register) {
+        return Ok(info);
+    }
+    let address = if let Some(hex) = register
+        .strip_prefix("0x")
+        .or_else(|| register.strip_prefix("0X"))
+    {
+        u8::from_str_radix(hex, 16)
+    } else if let Some(hex) = register
+        .strip_suffix('h')
+        .or_else(|| register.strip_suffix('H'))
+    {
+        u8::from_str_radix(hex, 16)
+    } else {
+        register.parse()
+    }
+    .map_err(|_| unknown())?;
+    register_map::by_address(address).ok_or_else(unknown)
+}
+
+/// Runs `command` against `axp`, writing its report to `out`.
+pub fn execute<I, E, W>(
+    command: &Command,
+    axp: &mut Axp192<I, E>,
+    out: &mut W,
+) -> Result<(), CliError<E>>
+where
+    I: RegisterInterface<AddressType = u8, Error = AxpError<E>>,
+    E: core::fmt::Debug,
+    W: Write,
+{
+    match *command {
+        Command::Status => status(axp, out),
+        Command::Dump => {
+            let dump = axp.dump_registers()?;
+            for (info, &(_, value)) in register_map::REGISTERS.iter().zip(dump.0.iter()) {
+                write_register(out, info, value)?;
+            }
+            Ok(())
+        }
+        Command::Get(info) => {
+            let value = read_register(axp, info)?;
+            write_register(out, info, value)
+        }
+        Command::Set { output, voltage_mv } => {
+            let programmed_mv = match output {
+                VoltageOutput::Rail(Rail::Dcdc(dc)) => {
+                    let raw = config::dcdc_rail_raw_from_mv(dc, voltage_mv)?;
+                    axp.set_dcdc_voltage(dc, voltage_mv)?;
+                    config::dcdc_mv_from_raw(raw)
+                }
+                VoltageOutput::Rail(Rail::Ldo(ldo)) => {
+                    let raw = config::ldo_raw_from_mv(voltage_mv)?;
+                    axp.set_ldo_voltage_mv(ldo, voltage_mv)?;
+                    config::ldo_mv_from_raw(raw)
+                }
+                VoltageOutput::Gpio0Ldo => {
+                    let raw = config::ldo_raw_from_mv(voltage_mv)?;
+                    axp.set_gpio0_ldo_voltage_mv(voltage_mv)?;
+                    config::ldo_mv_from_raw(raw)
+                }
+            };
+            writeln!(out, "{} set to {}mV", output_name(output), programmed_mv)?;
+            Ok(())
+        }
+        Command::Adc => {
+            let snapshot = axp.read_adc_snapshot()?;
+            write_adc(out, &snapshot)
+        }
+        Command::Irq { clear } => {
+            let enabled = axp.read_enabled_irqs()?;
+            let pending = axp.read_pending_irqs()?;
+            writeln!(out, "enabled: {:?}", enabled.iter().collect::<Vec<_>>())?;
+            writeln!(out, "pending: {:?}", pending.iter().collect::<Vec<_>>())?;
+            if clear && !pending.is_empty() {
+                axp.clear_irqs(pending)?;
+                writeln!(out, "cleared")?;
+            }
+            Ok(())
+        }
+        Command::Watch { .. } => {
+            let snapshot = axp.read_adc_snapshot()?;
+            let pending = axp.read_pending_irqs()?;
+            writeln!(
+                out,
+                "battery {} {} vbus {} {} acin {} {} temp {} irqs {:?}",
+                optional(snapshot.battery_voltage()),
+                optional(snapshot.battery_current()),
+                optional(snapshot.vbus_voltage()),
+                optional(snapshot.vbus_current()),
+                optional(snapshot.acin_voltage()),
+                optional(snapshot.acin_current()),
+                optional(snapshot.internal_temperature()),
+                pending.iter().collect::<Vec<_>>(),
+            )?;
+            Ok(())
+        }
+        Command::Help => {
+            out.write_all(USAGE.as_bytes())?;
+            Ok(())
+        }
+    }
+}
+
+fn charger_state_name(state: ChargerState) -> &'static str {
+    match state {
+        ChargerState::NoBattery => "no battery",
+        ChargerState::Discharging => "discharging",
+        ChargerState::PreCharge => "pre-charging",
+        ChargerState::ConstantCurrent => "charging (constant current)",
+        ChargerState::ConstantVoltage => "charging (constant voltage)",
+        ChargerState::Done => "charge done",
+        ChargerState::Disabled => "charger disabled",
+        ChargerState::Fault(ChargerFault::DieOverTemperature) => "fault: die over temperature",
+        ChargerState::Fault(ChargerFault::BatteryTemperature) => "fault: battery temperature",
+    }
+}
+
+fn status<I, E, W>(axp: &mut Axp192<I, E>, out: &mut W) -> Result<(), CliError<E>>
+where
+    I: RegisterInterface<AddressType = u8, Error = AxpError<E>>,
+    E: core::fmt::Debug,
+    W: Write,
+{
+    let power = axp.ll.power_status().read()?;
+    let charger = axp.charger_state()?;
+    let config = axp.read_config()?;
+    let snapshot = axp.read_adc_snapshot()?;
+
+    let input = |present: bool, usable: bool| match (present, usable) {
+        (false, _) => "absent",
+        (true, false) => "present, not usable",
+        (true, true) => "present, usable",
+    };
+    writeln!(
+        out,
+        "ACIN:    {}",
+        input(power.acin_present(), power.acin_usable())
+    )?;
+    writeln!(
+        out,
+        "VBUS:    {}",
+        input(power.vbus_present(), power.vbus_usable())
+    )?;
+    if charger != ChargerState::NoBattery {
+        writeln!(
+            out,
+            "Battery: {}, {}, {}",
+            optional(snapshot.battery_voltage()),
+            optional(snapshot.battery_current()),
+            charger_state_name(charger)
+        )?;
+    } else {
+        writeln!(out, "Battery: absent")?;
+    }
+    writeln!(out, "APS:     {}", optional(snapshot.aps_voltage()))?;
+    writeln!(
+        out,
+        "Die:     {}",
+        optional(snapshot.internal_temperature())
+    )?;
+
+    let rails = [
+        ("DCDC1", config.dcdc1),
+        ("DCDC2", config.dcdc2),
+        ("DCDC3", config.dcdc3),
+        ("LDO2", config.ldo2),
+        ("LDO3", config.ldo3),
+    ];
+    for (name, rail) in rails {
+        write_rail(out, name, rail)?;
+    }
+    if let Some(rail) = config.gpio0_ldo {
+        write_rail(out, "GPIO0", rail)?;
+    }
+    writeln!(out, "EXTEN:   {}", if config.exten { "on" } else { "off" })?;
+    Ok(())
+}
+
+fn write_rail<W: Write>(out: &mut W, name: &str, rail: RailConfig) -> std::io::Result<()> {
+    let state = if rail.enabled { "on" } else { "off" };
+    writeln!(
+        out,
+        "{:<8} {:<3} {}mV",
+        format!("{name}:"),
+        state,
+        rail.voltage_mv
+    )
+}
+
+fn write_adc<E: core::fmt::Debug, W: Write>(
+    out: &mut W,
+    snapshot: &AdcSnapshot,
+) -> Result<(), CliError<E>> {
+    writeln!(
+        out,
+        "ACIN voltage:      {}",
+        optional(snapshot.acin_voltage())
+    )?;
+    writeln!(
+        out,
+        "ACIN current:      {}",
+        optional(snapshot.acin_current())
+    )?;
+    writeln!(
+        out,
+        "VBUS voltage:      {}",
+        optional(snapshot.vbus_voltage())
+    )?;
+    writeln!(
+        out,
+        "VBUS current:      {}",
+        optional(snapshot.vbus_current())
+    )?;
+    writeln!(
+        out,
+        "Battery voltage:   {}",
+        optional(snapshot.battery_voltage())
+    )?;
+    writeln!(
+        out,
+        "Battery charge:    {}",
+        optional(snapshot.battery_charge_current())
+    )?;
+    writeln!(
+        out,
+        "Battery discharge: {}",
+        optional(snapshot.battery_discharge_current())
+    )?;
+    writeln!(
+        out,
+        "Battery power:     {}",
+        optional(snapshot.battery_instantaneous_power())
+    )?;
+    writeln!(
+        out,
+        "APS voltage:       {}",
+        optional(snapshot.aps_voltage())
+    )?;
+    writeln!(
+        out,
+        "Die temperature:   {}",
+        optional(snapshot.internal_temperature())
+    )?;
+    writeln!(
+        out,
+        "TS pin voltage:    {}",
+        optional(snapshot.ts_pin_voltage())
+    )?;
+    for (index, gpio) in [
+        GpioAdcId::Gpio0,
+        GpioAdcId::Gpio1,
+        GpioAdcId::Gpio2,
+        GpioAdcId::Gpio3,
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        writeln!(
+            out,
+            "GPIO{} voltage:     {}",
+            index,
+            optional(snapshot.gpio_voltage(gpio))
+        )?;
+    }
+    Ok(())
+}
+
+fn read_register<I, E>(axp: &mut Axp192<I, E>, info: &RegisterInfo) -> Result<u32, AxpError<E>>
+where
+    I: RegisterInterface<AddressType = u8, Error = AxpError<E>>,
+    E: core::fmt::Debug,
+{
+    let mut bytes = [0u8; 4];
+    let size = info.size_bytes as usize;
+    axp.ll
+        .interface()
+        .read_register(info.address, size as u32 * 8, &mut bytes[4 - size..])?;
+    Ok(u32::from_be_bytes(bytes))
+}
+
+fn write_register<E: core::fmt::Debug, W: Write>(
+    out: &mut W,
+    info: &RegisterInfo,
+    value: u32,
+) -> Result<(), CliError<E>> {
+    writeln!(
+        out,
+        "{:#04x}  {:<33} {:#0width$x}",
+        info.address,
+        info.name,
+        value,
+        width = 2 + info.size_bytes as usize * 2
+    )?;
+    for field in register_map::fields(info.address) {
+        writeln!(
+            out,
+            "  {}.{} = {}",
+            info.name,
+            field.name,
+            field.extract(value)
+        )?;
+    }
+    Ok(())
+}
+
+fn output_name(output: VoltageOutput) -> &'static str {
+    match output {
+        VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc1)) => "DCDC1",
+        VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc2)) => "DCDC2",
+        VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc3)) => "DCDC3",
+        VoltageOutput::Rail(Rail::Ldo(LdoId::Ldo2)) => "LDO2",
+        VoltageOutput::Rail(Rail::Ldo(LdoId::Ldo3)) => "LDO3",
+        VoltageOutput::Gpio0Ldo => "GPIO0 LDO",
+    }
+}
+
+/// A measurement, or `disabled` when its ADC channel is off.
+fn optional<T: core::fmt::Display>(value: Option<T>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("disabled"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AxpIrq;
+    use embedded_hal::i2c::ErrorKind;
+
+    use crate::sim::{SimAxp192, SimSupply};
+
+    fn command(args: &str) -> Result<Command, ParseError> {
+        parse(args.split_whitespace()).map(|args| args.command)
+    }
+
+    fn run(sim: &mut SimAxp192, args: &str) -> Result<String, CliError<ErrorKind>> {
+        let command = command(args).unwrap();
+        let mut axp = Axp192::new(sim);
+        let mut out = Vec::new();
+        execute(&command, &mut axp, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn parses_the_bus_option() {
+        ::core::assert_eq!(parse(["status"]).unwrap().bus, DEFAULT_BUS);
+        let args = parse(["--bus", "/dev/i2c-0", "dump"]).unwrap();
+        ::core::assert_eq!(args.bus, "/dev/i2c-0");
+        ::core::assert_eq!(args.command, Command::Dump);
+        ::core::assert_eq!(
+            parse(["-b", "/dev/i2c-3", "adc"]).unwrap().bus,
+            "/dev/i2c-3"
+        );
+        ::core::assert_eq!(
+            parse(["--bus"]),
+            Err(ParseError::MissingArgument("bus path"))
+        );
+        ::core::assert_eq!(parse(["-b", "/dev/i2c-0"]), Err(ParseError::MissingCommand));
+        ::core::assert_eq!(parse::<_, &str>([]), Err(ParseError::MissingCommand));
+    }
+
+    #[test]
+    fn parses_every_command() {
+        ::core::assert_eq!(command("status"), Ok(Command::Status));
+        ::core::assert_eq!(command("dump"), Ok(Command::Dump));
+        ::core::assert_eq!(command("adc"), Ok(Command::Adc));
+        ::core::assert_eq!(command("irq"), Ok(Command::Irq { clear: false }));
+        ::core::assert_eq!(command("irq clear"), Ok(Command::Irq { clear: true }));
+        for help in ["help", "-h", "--help"] {
+            ::core::assert_eq!(command(help), Ok(Command::Help));
+        }
+        ::core::assert_eq!(
+            command("frobnicate"),
+            Err(ParseError::UnknownCommand("frobnicate".into()))
+        );
+        ::core::assert_eq!(
+            command("status now"),
+            Err(ParseError::UnexpectedArgument("now".into()))
+        );
+        ::core::assert_eq!(
+            command("irq flush"),
+            Err(ParseError::UnexpectedArgument("flush".into()))
+        );
+    }
+
+    #[test]
+    fn parses_get_by_name_or_address() {
+        let power_status = register_map::by_name("PowerStatus").unwrap();
+        for register in ["PowerStatus", "powerstatus", "0x00", "0X00", "00h", "0"] {
+            ::core::assert_eq!(
+                command(&format!("get {register}")),
+                Ok(Command::Get(power_status))
+            );
+        }
+        // Inner bytes of a multi-byte register resolve to the register.
+        ::core::assert_eq!(
+            command("get 0x79"),
+            Ok(Command::Get(
+                register_map::by_name("BatteryVoltageAdc").unwrap()
+            ))
+        );
+        ::core::assert_eq!(command("get"), Err(ParseError::MissingArgument("register")));
+        ::core::assert_eq!(
+            command("get NoSuchRegister"),
+            Err(ParseError::UnknownRegister("NoSuchRegister".into()))
+        );
+        ::core::assert_eq!(
+            command("get 0x100"),
+            Err(ParseError::UnknownRegister("0x100".into()))
+        );
+    }
+
+    #[test]
+    fn parses_set() {
+        ::core::assert_eq!(
+            command("set DCDC2 1800mV"),
+            Ok(Command::Set {
+                output: VoltageOutput::Rail(Rail::Dcdc(DcId::Dcdc2)),
+                voltage_mv: 1800,
+            })
+        );
+        ::core::assert_eq!(
+            command("set ldoio0 2800"),
+            Ok(Command::Set {
+                output: VoltageOutput::Gpio0Ldo,
+                voltage_mv: 2800,
+            })
+        );
+        ::core::assert_eq!(command("set"), Err(ParseError::MissingArgument("rail")));
+        ::core::assert_eq!(
+            command("set ldo1 3300"),
+            Err(ParseError::UnknownRail("ldo1".into()))
+        );
+        ::core::assert_eq!(
+            command("set ldo2"),
+            Err(ParseError::MissingArgument("voltage"))
+        );
+        ::core::assert_eq!(
+            command("set ldo2 3.3V"),
+            Err(ParseError::InvalidNumber("3.3V".into()))
+        );
+    }
+
+    #[test]
+    fn parses_watch() {
+        ::core::assert_eq!(
+            command("watch"),
+            Ok(Command::Watch {
+                interval_ms: DEFAULT_WATCH_INTERVAL_MS
+            })
+        );
+        ::core::assert_eq!(
+            command("watch 250"),
+            Ok(Command::Watch { interval_ms: 250 })
+        );
+        ::core::assert_eq!(
+            command("watch 0"),
+            Err(ParseError::IntervalTooShort("0".into()))
+        );
+        ::core::assert_eq!(
+            command("watch -1"),
+            Err(ParseError::InvalidNumber("-1".into()))
+        );
+    }
+
+    #[test]
+    fn status_reports_inputs_battery_and_rails() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3700));
+        sim.set_vbus(Some(SimSupply::new(5000, 300)));
+        let out = run(&mut sim, "status").unwrap();
+        ::core::assert!(out.contains("ACIN:    absent\n"), "{out}");
+        ::core::assert!(out.contains("VBUS:    present, usable\n"), "{out}");
+        ::core::assert!(
+            out.contains("Battery: 3699mV, disabled, charging (constant current)\n"),
+            "{out}"
+        );
+        ::core::assert!(out.contains("DCDC1:   on  3300mV\n"), "{out}");
+        ::core::assert!(out.contains("LDO3:    on  3300mV\n"), "{out}");
+        ::core::assert!(!out.contains("GPIO0"), "{out}");
+        ::core::assert!(out.ends_with("EXTEN:   on\n"), "{out}");
+
+        sim.set_battery(None);
+        let out = run(&mut sim, "status").unwrap();
+        ::core::assert!(out.contains("Battery: absent\n"), "{out}");
+    }
+
+    #[test]
+    fn dump_and_get_print_decoded_registers() {
+        let mut sim = SimAxp192::new();
+        let out = run(&mut sim, "dump").unwrap();
+        let field_count: usize = register_map::REGISTERS
+            .iter()
+            .map(|info| register_map::fields(info.address).len())
+            .sum();
+        ::core::assert_eq!(
+            out.lines().count(),
+            register_map::REGISTERS.len() + field_count
+        );
+        ::core::assert!(
+            out.starts_with(
+                "0x00  PowerStatus                       0x00\n  PowerStatus.acin_present = 0\n"
+            ),
+            "{out}"
+        );
+
+        let out = run(&mut sim, "get ChargeControl1").unwrap();
+        ::core::assert_eq!(
+            out.lines().collect::<Vec<_>>(),
+            [
+                "0x33  ChargeControl1                    0xc8",
+                "  ChargeControl1.charge_enable = 1",
+                "  ChargeControl1.target_voltage = 2",
+                "  ChargeControl1.end_current_threshold = 0",
+                "  ChargeControl1.charge_current = 8",
+            ]
+        );
+    }
+
+    #[test]
+    fn set_prints_the_programmed_voltage() {
+        let mut sim = SimAxp192::new();
+        ::core::assert_eq!(
+            run(&mut sim, "set dcdc1 3333").unwrap(),
+            "DCDC1 set to 3325mV\n"
+        );
+        ::core::assert_eq!(sim.register(0x26), 105);
+        ::core::assert_eq!(
+            run(&mut sim, "set dcdc2 2275").unwrap(),
+            "DCDC2 set to 2275mV\n"
+        );
+        ::core::assert_eq!(sim.register(0x23), 63);
+        ::core::assert_eq!(
+            run(&mut sim, "set ldo2 2850").unwrap(),
+            "LDO2 set to 2800mV\n"
+        );
+        ::core::assert_eq!(sim.register(0x28) >> 4, 10);
+        ::core::assert_eq!(
+            run(&mut sim, "set gpio0 1800").unwrap(),
+            "GPIO0 LDO set to 1800mV\n"
+        );
+        ::core::assert_eq!(sim.register(0x91) >> 4, 0);
+    }
+
+    #[test]
+    fn set_rejects_voltages_outside_the_rail_range() {
+        let mut sim = SimAxp192::new();
+        for (args, voltage_mv) in [
+            ("set dcdc2 2300", 2300),
+            ("set dcdc1 3600", 3600),
+            ("set dcdc3 600", 600),
+            ("set ldo3 3400", 3400),
+            ("set gpio0 1700", 1700),
+        ] {
+            ::core::assert!(
+                matches!(
+                    run(&mut sim, args),
+                    Err(CliError::Axp(AxpError::InvalidVoltage(mv))) if mv == voltage_mv
+                ),
+                "{args}"
+            );
+        }
+        // DCDC2 keeps its reset voltage of 1250mV.
+        ::core::assert_eq!(sim.register(0x23), 0x16);
+    }
+
+    #[test]
+    fn adc_prints_every_channel() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3700));
+        let out = run(&mut sim, "adc").unwrap();
+        ::core::assert!(out.contains("Battery voltage:   3699mV\n"), "{out}");
+        ::core::assert!(out.contains("VBUS voltage:      disabled\n"), "{out}");
+        ::core::assert!(out.contains("Die temperature:   25.0C\n"), "{out}");
+        ::core::assert!(out.ends_with("GPIO3 voltage:     disabled\n"), "{out}");
+    }
+
+    #[test]
+    fn irq_lists_and_clears_pending_irqs() {
+        let mut sim = SimAxp192::new();
+        sim.raise_irq(AxpIrq::PekShortPress);
+        let out = run(&mut sim, "irq").unwrap();
+        ::core::assert!(out.contains("PekShortPress, PekLongPress"), "{out}");
+        ::core::assert!(out.ends_with("pending: [PekShortPress]\n"), "{out}");
+        ::core::assert!(sim.irq_pending(AxpIrq::PekShortPress));
+
+        let out = run(&mut sim, "irq clear").unwrap();
+        ::core::assert!(
+            out.ends_with("pending: [PekShortPress]\ncleared\n"),
+            "{out}"
+        );
+        ::core::assert!(!sim.irq_pending(AxpIrq::PekShortPress));
+        ::core::assert!(
+            run(&mut sim, "irq clear")
+                .unwrap()
+                .ends_with("pending: []\n")
+        );
+    }
+
+    #[test]
+    fn watch_prints_one_sample() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3700));
+        sim.raise_irq(AxpIrq::ChargeStarted);
+        ::core::assert_eq!(
+            run(&mut sim, "watch 100").unwrap(),
+            "battery 3699mV disabled vbus disabled disabled acin disabled disabled temp 25.0C irqs [BatteryInserted, ChargeStarted]\n"
+        );
+    }
+
+    #[test]
+    fn help_prints_usage() {
+        let mut sim = SimAxp192::new();
+        ::core::assert_eq!(run(&mut sim, "help").unwrap(), USAGE);
+    }
+}
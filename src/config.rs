@@ -0,0 +1,174 @@
+//! Declarative power-rail configuration.
+//!
+//! A [`PowerConfig`] describes the state of every regulator the AXP192 controls.
+//! It is plain data with `const` constructors, so a board's configuration can be
+//! a `static` in flash and applied at boot with `Axp192::apply_config`, which
+//! only writes registers whose contents differ from the requested state.
+//! `Axp192::read_config` reads the same description back from the chip.
+
+use crate::{AxpError, ChargerConfig, DcDcModeSelection, DcId, PekConfig};
+
+/// Enable state and output voltage of one regulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RailConfig {
+    pub enabled: bool,
+    pub voltage_mv: u16,
+}
+
+impl RailConfig {
+    /// An enabled rail at `voltage_mv`.
+    pub const fn on(voltage_mv: u16) -> Self {
+        Self {
+            enabled: true,
+            voltage_mv,
+        }
+    }
+
+    /// A disabled rail. The voltage is still programmed, so it applies once enabled.
+    pub const fn off(voltage_mv: u16) -> Self {
+        Self {
+            enabled: false,
+            voltage_mv,
+        }
+    }
+}
+
+/// Regulator configuration of the whole chip.
+///
+/// Voltages are truncated to the regulator step size: 25mV for the DC-DCs and
+/// 100mV for LDO2/LDO3 and the GPIO0 LDO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PowerConfig {
+    /// DC-DC1, 0.7V-3.5V.
+    pub dcdc1: RailConfig,
+    /// DC-DC2, 0.7V-2.275V.
+    pub dcdc2: RailConfig,
+    /// DC-DC3, 0.7V-3.5V.
+    pub dcdc3: RailConfig,
+    /// LDO2, 1.8V-3.3V.
+    pub ldo2: RailConfig,
+    /// LDO3, 1.8V-3.3V.
+    pub ldo3: RailConfig,
+    /// GPIO0 in low-noise LDO (LDOio0) mode, 1.8V-3.3V. `None` leaves GPIO0
+    /// untouched; a disabled rail floats GPIO0 if it is currently in LDO mode.
+    pub gpio0_ldo: Option<RailConfig>,
+    pub exten: bool,
+    pub dcdc1_mode: DcDcModeSelection,
+    pub dcdc2_mode: DcDcModeSelection,
+    pub dcdc3_mode: DcDcModeSelection,
+    /// DC-DC switching frequency, 900kHz-2025kHz in 75kHz steps.
+    pub dcdc_frequency_khz: u16,
+    /// Charger settings; `None` leaves the charger untouched.
+    pub charger: Option<ChargerConfig>,
+    /// Power key timings; `None` leaves them untouched.
+    pub pek: Option<PekConfig>,
+}
+
+impl PowerConfig {
+    /// The register reset values listed in `device.yaml`.
+    pub const DEFAULT: PowerConfig = PowerConfig {
+        dcdc1: RailConfig::on(3300),
+        dcdc2: RailConfig::on(1250),
+        dcdc3: RailConfig::on(2500),
+        ldo2: RailConfig::on(3000),
+        ldo3: RailConfig::on(3300),
+        gpio0_ldo: None,
+        exten: true,
+        dcdc1_mode: DcDcModeSelection::AutoPfmPwm,
+        dcdc2_mode: DcDcModeSelection::AutoPfmPwm,
+        dcdc3_mode: DcDcModeSelection::AutoPfmPwm,
+        dcdc_frequency_khz: 1500,
+        charger: None,
+        pek: None,
+    };
+
+    /// Checks every value and converts it to its register encoding.
+    pub(crate) fn encode<E>(&self) -> Result<RawPowerConfig, AxpError<E>> {
+        let gpio0_ldo_raw = match self.gpio0_ldo {
+            Some(rail) => Some(ldo_raw_from_mv(rail.voltage_mv)?),
+            None => None,
+        };
+        if let Some(charger) = &self.charger {
+            charger.encode()?;
+        }
+        Ok(RawPowerConfig {
+            dcdc1: dcdc_raw_from_mv(self.dcdc1.voltage_mv, DCDC1_3_MAX_MV)?,
+            dcdc2: dcdc_raw_from_mv(self.dcdc2.voltage_mv, DCDC2_MAX_MV)?,
+            dcdc3: dcdc_raw_from_mv(self.dcdc3.voltage_mv, DCDC1_3_MAX_MV)?,
+            ldo2: ldo_raw_from_mv(self.ldo2.voltage_mv)?,
+            ldo3: ldo_raw_from_mv(self.ldo3.voltage_mv)?,
+            gpio0_ldo: gpio0_ldo_raw,
+            frequency: frequency_raw_from_khz(self.dcdc_frequency_khz)?,
+        })
+    }
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Register encodings of a validated [`PowerConfig`].
+pub(crate) struct RawPowerConfig {
+    pub dcdc1: u8,
+    pub dcdc2: u8,
+    pub dcdc3: u8,
+    pub ldo2: u8,
+    pub ldo3: u8,
+    pub gpio0_ldo: Option<u8>,
+    pub frequency: u8,
+}
+
+const DCDC_MIN_MV: u16 = 700;
+const DCDC1_3_MAX_MV: u16 = 3500;
+const DCDC2_MAX_MV: u16 = 2275;
+const LDO_MIN_MV: u16 = 1800;
+const LDO_MAX_MV: u16 = 3300;
+const FREQUENCY_MIN_KHZ: u16 = 900;
+const FREQUENCY_MAX_KHZ: u16 = 2025;
+
+pub(crate) const fn dcdc_mv_from_raw(raw: u8) -> u16 {
+    DCDC_MIN_MV + raw as u16 * 25
+}
+
+pub(crate) const fn ldo_mv_from_raw(raw: u8) -> u16 {
+    LDO_MIN_MV + raw as u16 * 100
+}
+
+pub(crate) const fn frequency_khz_from_raw(raw: u8) -> u16 {
+    FREQUENCY_MIN_KHZ + raw as u16 * 75
+}
+
+/// Register encoding of `voltage_mv` for DC-DC `dc`, checked against that
+/// rail's range.
+pub(crate) fn dcdc_rail_raw_from_mv<E>(dc: DcId, voltage_mv: u16) -> Result<u8, AxpError<E>> {
+    let max_mv = match dc {
+        DcId::Dcdc2 => DCDC2_MAX_MV,
+        DcId::Dcdc1 | DcId::Dcdc3 => DCDC1_3_MAX_MV,
+    };
+    dcdc_raw_from_mv(voltage_mv, max_mv)
+}
+
+fn dcdc_raw_from_mv<E>(voltage_mv: u16, max_mv: u16) -> Result<u8, AxpError<E>> {
+    if !(DCDC_MIN_MV..=max_mv).contains(&voltage_mv) {
+        return Err(AxpError::InvalidVoltage(voltage_mv));
+    }
+    Ok(((voltage_mv - DCDC_MIN_MV) / 25) as u8)
+}
+
+pub(crate) fn ldo_raw_from_mv<E>(voltage_mv: u16) -> Result<u8, AxpError<E>> {
+    if !(LDO_MIN_MV..=LDO_MAX_MV).contains(&voltage_mv) {
+        return Err(AxpError::InvalidVoltage(voltage_mv));
+    }
+    Ok(((voltage_mv - LDO_MIN_MV) / 100) as u8)
+}
+
+fn frequency_raw_from_khz<E>(frequency_khz: u16) -> Result<u8, AxpError<E>> {
+    if !(FREQUENCY_MIN_KHZ..=FREQUENCY_MAX_KHZ).contains(&frequency_khz) {
+        return Err(AxpError::InvalidFrequency(frequency_khz));
+    }
+    Ok(((frequency_khz - FREQUENCY_MIN_KHZ) / 75) as u8)
+}
@@ -1,5 +1,27 @@
 use super::{I2c, RegisterInterface, bisync, only_async, only_sync};
-use crate::{AXP192_I2C_ADDRESS, AxpError, AxpInterface, AxpLowLevel, DcId, LdoId, adc_helpers::*};
+use crate::adc_snapshot::{ADC_BLOCK_LEN, ADC_BLOCK_START, ADC_CONTROL_LEN, ADC_CONTROL_START};
+use crate::backup::{BACKUP_DATA_LEN, DATA_BUFFER_START, decode_record, encode_record};
+use crate::boards::{BACKLIGHT_MAX_MV, BACKLIGHT_MIN_MV, Rail};
+use crate::config;
+use crate::irq::{IRQ_ENABLE_ADDRESSES, IRQ_STATUS_ADDRESSES};
+#[cfg(feature = "float")]
+use crate::ntc::{Ntc, ntc_threshold_pair};
+use crate::ntc::{TemperatureWindow, temp_threshold_mv_from_raw, temp_threshold_raw_from_mv};
+use crate::pwm::{PWM_Y2_MAX, solve_frequency};
+use crate::register_map::{REGISTER_COUNT, REGISTERS};
+use crate::snapshot::{SNAPSHOT_REGISTER_COUNT, SNAPSHOT_REGISTERS};
+use crate::{
+    AXP192_I2C_ADDRESS, AdcSampleRateValue, AdcSnapshot, AxpError, AxpInterface, AxpIrq, AxpIrqSet,
+    AxpLowLevel, BackupError, BackupRecord, Board, ChargeCurrentValue,
+    ChargeEndCurrentThresholdValue, ChargeTargetVoltageValue, ChargerConfig, ChargerState,
+    ConstantCurrentTimeoutValue, CoulombCounts, DcId, DeciCelsius, ExternalPathChargeCurrentValue,
+    Gpio0FunctionSelect, Gpio1FunctionSelect, Gpio2FunctionSelect, Gpio3FunctionSetting,
+    Gpio4FunctionSetting, Gpio5Direction, GpioAdcId, GpioAdcRange, GpioId, GpioMode, LdoId,
+    MicroampHours, Microamps, Microwatts, Millivolts, NrstoPinFunction, PekConfig, PekPress,
+    PowerConfig, PrechargeTimeoutValue, PwmId, PwmParameters, RailConfig, RegisterDump,
+    RegisterSnapshot, SocEstimator, TsPinOutputMode, VoffVoltageValue, VoltageSocEstimator,
+    WakeConfig, adc_helpers::*, field_sets,
+};
 
 #[bisync]
 impl<I2CBus, E> RegisterInterface for AxpInterface<I2CBus>
@@ -26,7 +48,8 @@ where
         _size_bits: u32,
         data: &[u8],
     ) -> Result<(), Self::Error> {
-        let mut buffer = [0u8; 5];
+        // Register address plus the longest burst, the six `DataBuffer` registers.
+        let mut buffer = [0u8; 7];
         if (1 + data.len()) > buffer.len() {
             return Err(AxpError::NotSupported("Write data length exceeds buffer"));
         }
@@ -58,6 +81,18 @@ where
             _marker: core::marker::PhantomData,
         }
     }
+
+    /// Turns the driver into an [`AxpEventStream`](crate::AxpEventStream) fed by the AXP192 IRQ pin.
+    #[only_async]
+    pub fn into_event_stream<IrqPin>(
+        self,
+        irq_pin: IrqPin,
+    ) -> crate::AxpEventStream<AxpInterface<I2CBus>, E, IrqPin>
+    where
+        IrqPin: embedded_hal_async::digital::Wait,
+    {
+        crate::AxpEventStream::new(self, irq_pin)
+    }
 }
 
 pub trait CurrentAxpDriverInterface<E>:
@@ -79,28 +114,352 @@ where
     I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
     I2CBusErr: core::fmt::Debug,
 {
+    #[bisync]
+    pub async fn get_battery_voltage(&mut self) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(battery_voltage_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_battery_charge_current(&mut self) -> Result<Microamps, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_charge_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_13bit_from_raw_u16(raw_fieldset.raw());
+        Ok(battery_current_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_battery_discharge_current(
+        &mut self,
+    ) -> Result<Microamps, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_discharge_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_13bit_from_raw_u16(raw_fieldset.raw());
+        Ok(battery_current_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_battery_instantaneous_power(
+        &mut self,
+    ) -> Result<Microwatts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_instantaneous_power_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_24bit_from_raw_u32(raw_fieldset.raw());
+        Ok(battery_power_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_acin_voltage(&mut self) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.acin_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(acin_voltage_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_acin_current(&mut self) -> Result<Microamps, AxpError<I2CBusErr>> {
+        let mut op = self.ll.acin_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(acin_current_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_vbus_voltage(&mut self) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.vbus_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(vbus_voltage_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_vbus_current(&mut self) -> Result<Microamps, AxpError<I2CBusErr>> {
+        let mut op = self.ll.vbus_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(vbus_current_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_internal_temperature(&mut self) -> Result<DeciCelsius, AxpError<I2CBusErr>> {
+        let mut op = self.ll.internal_temperature_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(internal_temperature_from_adc(adc_val))
+    }
+
+    #[bisync]
+    pub async fn get_ts_pin_voltage(&mut self) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.ts_pin_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(ts_pin_voltage_from_adc(adc_val))
+    }
+
+    /// GPIO ADC voltage, including the 0.7V offset when the pin uses the high input range.
+    #[bisync]
+    pub async fn get_gpio_voltage(
+        &mut self,
+        gpio: GpioAdcId,
+    ) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let range = self.get_gpio_adc_range(gpio).await?;
+        let mut op = self.ll.gpio_voltage_adc(gpio as usize);
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(gpio_voltage_from_adc(adc_val, range))
+    }
+
+    #[bisync]
+    pub async fn get_aps_voltage(&mut self) -> Result<Millivolts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.aps_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(aps_voltage_from_adc(adc_val))
+    }
+
+    /// Net battery current, positive while charging and negative while discharging.
+    #[bisync]
+    pub async fn get_battery_current(&mut self) -> Result<Microamps, AxpError<I2CBusErr>> {
+        let charge = self.get_battery_charge_current().await?;
+        let discharge = self.get_battery_discharge_current().await?;
+        Ok(Microamps(charge.ua() - discharge.ua()))
+    }
+
+    #[cfg(feature = "float")]
     #[bisync]
     pub async fn get_battery_voltage_mv(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
         let mut op = self.ll.battery_voltage_adc();
         let raw_fieldset = read_internal(&mut op).await?;
         let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
-        Ok(adc_val as f32 * 1.1)
+        Ok(battery_voltage_mv_from_adc(adc_val))
     }
 
+    #[cfg(feature = "float")]
     #[bisync]
     pub async fn get_battery_charge_current_ma(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
         let mut op = self.ll.battery_charge_current_adc();
         let raw_fieldset = read_internal(&mut op).await?;
         let adc_val = adc_13bit_from_raw_u16(raw_fieldset.raw());
-        Ok(adc_val as f32 * 0.5)
+        Ok(battery_current_ma_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_battery_discharge_current_ma(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_discharge_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_13bit_from_raw_u16(raw_fieldset.raw());
+        Ok(battery_current_ma_from_adc(adc_val))
     }
 
+    #[cfg(feature = "float")]
     #[bisync]
     pub async fn get_battery_instantaneous_power_uw(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
         let mut op = self.ll.battery_instantaneous_power_adc();
         let raw_fieldset = read_internal(&mut op).await?;
         let adc_val = adc_24bit_from_raw_u32(raw_fieldset.raw());
-        Ok(adc_val as f32 * 0.55)
+        Ok(battery_power_uw_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_acin_voltage_mv(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.acin_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(acin_voltage_mv_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_acin_current_ma(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.acin_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(acin_current_ma_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_vbus_voltage_mv(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.vbus_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(vbus_voltage_mv_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_vbus_current_ma(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.vbus_current_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(vbus_current_ma_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_internal_temperature_c(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.internal_temperature_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(internal_temperature_c_from_adc(adc_val))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_ts_pin_voltage_mv(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.ts_pin_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(ts_pin_voltage_mv_from_adc(adc_val))
+    }
+
+    /// GPIO ADC voltage, including the 0.7V offset when the pin uses the high input range.
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_gpio_voltage_mv(
+        &mut self,
+        gpio: GpioAdcId,
+    ) -> Result<f32, AxpError<I2CBusErr>> {
+        let range = self.get_gpio_adc_range(gpio).await?;
+        let mut op = self.ll.gpio_voltage_adc(gpio as usize);
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(gpio_voltage_mv_from_adc(adc_val, range))
+    }
+
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_aps_voltage_mv(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let mut op = self.ll.aps_voltage_adc();
+        let raw_fieldset = read_internal(&mut op).await?;
+        let adc_val = adc_12bit_from_raw_u16(raw_fieldset.raw());
+        Ok(aps_voltage_mv_from_adc(adc_val))
+    }
+
+    /// Reads every ADC channel in two burst transfers. Disabled channels are `None`.
+    #[bisync]
+    pub async fn read_adc_snapshot(&mut self) -> Result<AdcSnapshot, AxpError<I2CBusErr>> {
+        let mut control = [0u8; ADC_CONTROL_LEN];
+        self.read_raw_registers(ADC_CONTROL_START, &mut control)
+            .await?;
+        let mut block = [0u8; ADC_BLOCK_LEN];
+        self.read_raw_registers(ADC_BLOCK_START, &mut block).await?;
+        Ok(AdcSnapshot::from_registers(&block, &control))
+    }
+
+    #[bisync]
+    pub async fn get_gpio_adc_range(
+        &mut self,
+        gpio: GpioAdcId,
+    ) -> Result<GpioAdcRange, AxpError<I2CBusErr>> {
+        let mut op = self.ll.gpio_adc_input_range_setting();
+        let r = read_internal(&mut op).await?;
+        Ok(match gpio {
+            GpioAdcId::Gpio0 => r.gpio_0_adc_input_range(),
+            GpioAdcId::Gpio1 => r.gpio_1_adc_input_range(),
+            GpioAdcId::Gpio2 => r.gpio_2_adc_input_range(),
+            GpioAdcId::Gpio3 => r.gpio_3_adc_input_range(),
+        })
+    }
+
+    /// Switches a GPIO to ADC input with the given input range and enables its
+    /// ADC channel, so [`Self::get_gpio_voltage`] returns fresh samples.
+    #[bisync]
+    pub async fn set_gpio_adc_input(
+        &mut self,
+        gpio: GpioAdcId,
+        range: GpioAdcRange,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        match gpio {
+            GpioAdcId::Gpio0 => {
+                let mut op = self.ll.gpio_0_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(Gpio0FunctionSelect::AdcInput)
+                })
+                .await?;
+            }
+            GpioAdcId::Gpio1 => {
+                let mut op = self.ll.gpio_1_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(Gpio1FunctionSelect::AdcInput)
+                })
+                .await?;
+            }
+            GpioAdcId::Gpio2 => {
+                let mut op = self.ll.gpio_2_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(Gpio2FunctionSelect::AdcInput)
+                })
+                .await?;
+            }
+            GpioAdcId::Gpio3 => {
+                let mut op = self.ll.gpio_3_and_4_function_control();
+                modify_internal(&mut op, |r| {
+                    r.set_gpio_3_and_4_mode_enable(true);
+                    r.set_gpio_3_function_select(Gpio3FunctionSetting::AdcInput);
+                })
+                .await?;
+            }
+        }
+        let mut op = self.ll.gpio_adc_input_range_setting();
+        modify_internal(&mut op, |r| match gpio {
+            GpioAdcId::Gpio0 => r.set_gpio_0_adc_input_range(range),
+            GpioAdcId::Gpio1 => r.set_gpio_1_adc_input_range(range),
+            GpioAdcId::Gpio2 => r.set_gpio_2_adc_input_range(range),
+            GpioAdcId::Gpio3 => r.set_gpio_3_adc_input_range(range),
+        })
+        .await?;
+        let mut op = self.ll.adc_enable_2();
+        modify_internal(&mut op, |r| match gpio {
+            GpioAdcId::Gpio0 => r.set_gpio_0_adc_enable(true),
+            GpioAdcId::Gpio1 => r.set_gpio_1_adc_enable(true),
+            GpioAdcId::Gpio2 => r.set_gpio_2_adc_enable(true),
+            GpioAdcId::Gpio3 => r.set_gpio_3_adc_enable(true),
+        })
+        .await
+    }
+
+    /// Sets the GPIO1 ADC window: crossings above `rising_mv` or below
+    /// `falling_mv` raise the `Gpio1Edge` IRQ.
+    ///
+    /// The thresholds follow the GPIO1 input range set with
+    /// [`Self::set_gpio_adc_input`]: 0-2040mV, or 700-2740mV in the high range,
+    /// in 8mV steps. Values outside return `AxpError::InvalidVoltage`.
+    #[bisync]
+    pub async fn set_gpio1_adc_irq_thresholds_mv(
+        &mut self,
+        rising_mv: u16,
+        falling_mv: u16,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let range = self.get_gpio_adc_range(GpioAdcId::Gpio1).await?;
+        let rising = gpio1_adc_threshold_raw_from_mv(rising_mv, range)
+            .ok_or(AxpError::InvalidVoltage(rising_mv))?;
+        let falling = gpio1_adc_threshold_raw_from_mv(falling_mv, range)
+            .ok_or(AxpError::InvalidVoltage(falling_mv))?;
+        let mut op = self.ll.gpio_1_adc_irq_rising_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(rising)).await?;
+        let mut op = self.ll.gpio_1_adc_irq_falling_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(falling)).await
+    }
+
+    /// The GPIO1 ADC IRQ thresholds as `(rising, falling)`, in the current input range.
+    #[bisync]
+    pub async fn get_gpio1_adc_irq_thresholds(
+        &mut self,
+    ) -> Result<(Millivolts, Millivolts), AxpError<I2CBusErr>> {
+        let range = self.get_gpio_adc_range(GpioAdcId::Gpio1).await?;
+        let mut op = self.ll.gpio_1_adc_irq_rising_threshold();
+        let rising = read_internal(&mut op).await?.threshold_setting_raw();
+        let mut op = self.ll.gpio_1_adc_irq_falling_threshold();
+        let falling = read_internal(&mut op).await?.threshold_setting_raw();
+        Ok((
+            Millivolts(gpio1_adc_threshold_mv_from_raw(rising, range) as u32),
+            Millivolts(gpio1_adc_threshold_mv_from_raw(falling, range) as u32),
+        ))
     }
 
     #[bisync]
@@ -118,16 +477,29 @@ where
         .await
     }
 
+    #[bisync]
+    pub async fn set_ldo_enable(
+        &mut self,
+        ldo: LdoId,
+        enable: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.power_output_control();
+        modify_internal(&mut op, |r| match ldo {
+            LdoId::Ldo2 => r.set_ldo_2_output_enable(enable),
+            LdoId::Ldo3 => r.set_ldo_3_output_enable(enable),
+        })
+        .await
+    }
+
+    /// Programs DC-DC1/DC-DC3 to 700-3500mV or DC-DC2 to 700-2275mV, truncated
+    /// to a 25mV step.
     #[bisync]
     pub async fn set_dcdc_voltage(
         &mut self,
         dc: DcId,
         voltage_mv: u16,
     ) -> Result<(), AxpError<I2CBusErr>> {
-        if !(700..=3500).contains(&voltage_mv) {
-            return Err(AxpError::InvalidVoltage(voltage_mv));
-        }
-        let raw_setting = ((voltage_mv.saturating_sub(700)) / 25) as u8;
+        let raw_setting = config::dcdc_rail_raw_from_mv(dc, voltage_mv)?;
 
         match dc {
             DcId::Dcdc1 => {
@@ -192,11 +564,8 @@ where
         &mut self,
         threshold_mv: u16,
     ) -> Result<(), AxpError<I2CBusErr>> {
-        if threshold_mv > 3264 {
-            return Err(AxpError::InvalidVoltage(threshold_mv));
-        }
-        let raw_setting_u16 = (threshold_mv * 10 + 64) / 128;
-        let raw_setting = raw_setting_u16 as u8;
+        let raw_setting = temp_threshold_raw_from_mv(threshold_mv)
+            .ok_or(AxpError::InvalidVoltage(threshold_mv))?;
 
         let mut op = self.ll.battery_charge_high_temp_threshold();
         write_internal(&mut op, |r| {
@@ -210,11 +579,8 @@ where
         &mut self,
         threshold_mv: u16,
     ) -> Result<(), AxpError<I2CBusErr>> {
-        if threshold_mv > 3264 {
-            return Err(AxpError::InvalidVoltage(threshold_mv));
-        }
-        let raw_setting_u16 = (threshold_mv * 10 + 64) / 128;
-        let raw_setting = raw_setting_u16 as u8;
+        let raw_setting = temp_threshold_raw_from_mv(threshold_mv)
+            .ok_or(AxpError::InvalidVoltage(threshold_mv))?;
 
         let mut op = self.ll.battery_charge_low_temp_threshold();
         write_internal(&mut op, |r| {
@@ -222,4 +588,1265 @@ where
         })
         .await
     }
+
+    /// Programs the charge and discharge temperature thresholds.
+    ///
+    /// Both windows are checked before anything is written; a low-temperature
+    /// threshold not above its high-temperature threshold returns
+    /// `AxpError::InvertedTemperatureWindow`.
+    #[bisync]
+    pub async fn set_temperature_window(
+        &mut self,
+        window: &TemperatureWindow,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let [charge_low, charge_high, discharge_low, discharge_high] = window.encode()?;
+        let mut op = self.ll.battery_charge_low_temp_threshold();
+        modify_if_changed_internal(&mut op, |r| r.set_threshold_setting_raw(charge_low)).await?;
+        let mut op = self.ll.battery_charge_high_temp_threshold();
+        modify_if_changed_internal(&mut op, |r| r.set_threshold_setting_raw(charge_high)).await?;
+        let mut op = self.ll.battery_discharge_low_temp_threshold();
+        modify_if_changed_internal(&mut op, |r| r.set_threshold_setting_raw(discharge_low)).await?;
+        let mut op = self.ll.battery_discharge_high_temp_threshold();
+        modify_if_changed_internal(&mut op, |r| r.set_threshold_setting_raw(discharge_high))
+            .await?;
+        Ok(())
+    }
+
+    /// Reads back the temperature thresholds in mV.
+    #[bisync]
+    pub async fn get_temperature_window(
+        &mut self,
+    ) -> Result<TemperatureWindow, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_charge_low_temp_threshold();
+        let charge_low = read_internal(&mut op).await?;
+        let mut op = self.ll.battery_charge_high_temp_threshold();
+        let charge_high = read_internal(&mut op).await?;
+        let mut op = self.ll.battery_discharge_low_temp_threshold();
+        let discharge_low = read_internal(&mut op).await?;
+        let mut op = self.ll.battery_discharge_high_temp_threshold();
+        let discharge_high = read_internal(&mut op).await?;
+        Ok(TemperatureWindow {
+            charge_low_temp_mv: temp_threshold_mv_from_raw(charge_low.threshold_setting_raw()),
+            charge_high_temp_mv: temp_threshold_mv_from_raw(charge_high.threshold_setting_raw()),
+            discharge_low_temp_mv: temp_threshold_mv_from_raw(
+                discharge_low.threshold_setting_raw(),
+            ),
+            discharge_high_temp_mv: temp_threshold_mv_from_raw(
+                discharge_high.threshold_setting_raw(),
+            ),
+        })
+    }
+
+    /// Sets up the TS pin for `ntc`: battery temperature monitoring at its bias
+    /// current, with the TS pin ADC enabled.
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn configure_ntc(&mut self, ntc: &Ntc) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.adc_sample_rate_ts_pin_control();
+        modify_internal(&mut op, |r| {
+            r.set_ts_pin_output_current(ntc.bias_current);
+            r.set_ts_pin_function_is_external_adc(false);
+        })
+        .await?;
+        let mut op = self.ll.adc_enable_1();
+        modify_internal(&mut op, |r| r.set_ts_pin_adc_enable(true)).await
+    }
+
+    /// Battery temperature from the TS pin ADC, `None` if the pin reads open or shorted.
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_battery_temperature_c(
+        &mut self,
+        ntc: &Ntc,
+    ) -> Result<Option<f32>, AxpError<I2CBusErr>> {
+        let pin_mv = self.get_ts_pin_voltage_mv().await?;
+        Ok(ntc.temperature_from_mv(pin_mv))
+    }
+
+    /// Sets the battery temperature range in which charging is allowed.
+    ///
+    /// The thresholds are the TS pin voltages of `ntc` at `min_c` and `max_c`;
+    /// returns `AxpError::InvalidVoltage` if either is above 3264mV and
+    /// `AxpError::InvertedTemperatureWindow` unless `min_c` is below `max_c`.
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn set_charge_temperature_window_c(
+        &mut self,
+        ntc: &Ntc,
+        min_c: f32,
+        max_c: f32,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let (low_raw, high_raw) = ntc_threshold_pair(ntc, min_c, max_c)?;
+        let mut op = self.ll.battery_charge_low_temp_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(low_raw)).await?;
+        let mut op = self.ll.battery_charge_high_temp_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(high_raw)).await
+    }
+
+    /// Sets the battery temperature range in which discharging is allowed.
+    ///
+    /// Same conversion as [`set_charge_temperature_window_c`](Self::set_charge_temperature_window_c).
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn set_discharge_temperature_window_c(
+        &mut self,
+        ntc: &Ntc,
+        min_c: f32,
+        max_c: f32,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let (low_raw, high_raw) = ntc_threshold_pair(ntc, min_c, max_c)?;
+        let mut op = self.ll.battery_discharge_low_temp_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(low_raw)).await?;
+        let mut op = self.ll.battery_discharge_high_temp_threshold();
+        write_internal(&mut op, |r| r.set_threshold_setting_raw(high_raw)).await
+    }
+
+    #[bisync]
+    pub async fn set_charge_enable(&mut self, enable: bool) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.charge_control_1();
+        modify_internal(&mut op, |r| r.set_charge_enable(enable)).await
+    }
+
+    /// Sets the internal path charge current to the highest step not exceeding
+    /// `current_ma`, which must be within 100-1320mA.
+    #[bisync]
+    pub async fn set_charge_current_ma(
+        &mut self,
+        current_ma: u16,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let current =
+            ChargeCurrentValue::from_ma(current_ma).ok_or(AxpError::InvalidCurrent(current_ma))?;
+        let mut op = self.ll.charge_control_1();
+        modify_internal(&mut op, |r| r.set_charge_current(current)).await
+    }
+
+    /// Sets the charge target voltage: 4100, 4150, 4200 or 4360mV.
+    #[bisync]
+    pub async fn set_charge_target_voltage_mv(
+        &mut self,
+        voltage_mv: u16,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let voltage = ChargeTargetVoltageValue::from_mv(voltage_mv)
+            .ok_or(AxpError::InvalidVoltage(voltage_mv))?;
+        let mut op = self.ll.charge_control_1();
+        modify_internal(&mut op, |r| r.set_target_voltage(voltage)).await
+    }
+
+    /// Sets the current, as a percentage of the charge current, below which
+    /// charging ends: 10 or 15. Other values return `InvalidPercentage(percent)`.
+    #[bisync]
+    pub async fn set_charge_termination_percent(
+        &mut self,
+        percent: u8,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let threshold = ChargeEndCurrentThresholdValue::from_percent(percent)
+            .ok_or(AxpError::InvalidPercentage(percent))?;
+        let mut op = self.ll.charge_control_1();
+        modify_internal(&mut op, |r| r.set_end_current_threshold(threshold)).await
+    }
+
+    #[bisync]
+    pub async fn set_precharge_timeout(
+        &mut self,
+        timeout: PrechargeTimeoutValue,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.charge_control_2();
+        modify_internal(&mut op, |r| r.set_precharge_timeout(timeout)).await
+    }
+
+    /// Sets the constant-current phase timeout.
+    #[bisync]
+    pub async fn set_cc_timeout(
+        &mut self,
+        timeout: ConstantCurrentTimeoutValue,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.charge_control_2();
+        modify_internal(&mut op, |r| r.set_constant_current_timeout(timeout)).await
+    }
+
+    /// Enables or disables charging through the external path transistor,
+    /// with its current rounded down to a 100mA step within 300-1000mA.
+    #[bisync]
+    pub async fn set_external_path_charge(
+        &mut self,
+        enable: bool,
+        current_ma: u16,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let current = ExternalPathChargeCurrentValue::from_ma(current_ma)
+            .ok_or(AxpError::InvalidCurrent(current_ma))?;
+        let mut op = self.ll.charge_control_2();
+        modify_internal(&mut op, |r| {
+            r.set_external_path_charge_enable(enable);
+            r.set_external_path_charge_current(current);
+        })
+        .await
+    }
+
+    /// Writes the whole charger configuration, validating it first. Registers
+    /// that already hold the requested values are not written.
+    #[bisync]
+    pub async fn set_charger_config(
+        &mut self,
+        charger: &ChargerConfig,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let raw = charger.encode()?;
+        let mut op = self.ll.charge_control_1();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_charge_enable(charger.enabled);
+            r.set_target_voltage(raw.target_voltage);
+            r.set_end_current_threshold(charger.termination);
+            r.set_charge_current(raw.current);
+        })
+        .await?;
+        let mut op = self.ll.charge_control_2();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_precharge_timeout(charger.precharge_timeout);
+            r.set_external_path_charge_current(raw.external_path_current);
+            r.set_external_path_charge_enable(charger.external_path_enabled);
+            r.set_constant_current_timeout(charger.cc_timeout);
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[bisync]
+    pub async fn get_charger_config(&mut self) -> Result<ChargerConfig, AxpError<I2CBusErr>> {
+        let mut op = self.ll.charge_control_1();
+        let charge_1 = read_internal(&mut op).await?;
+        let mut op = self.ll.charge_control_2();
+        let charge_2 = read_internal(&mut op).await?;
+        Ok(ChargerConfig {
+            enabled: charge_1.charge_enable(),
+            target_voltage_mv: charge_1.target_voltage().mv(),
+            current_ma: charge_1.charge_current().ma(),
+            termination: charge_1.end_current_threshold(),
+            precharge_timeout: charge_2.precharge_timeout(),
+            cc_timeout: charge_2.constant_current_timeout(),
+            external_path_enabled: charge_2.external_path_charge_enable(),
+            external_path_current_ma: charge_2.external_path_charge_current().ma(),
+        })
+    }
+
+    /// Decodes the charger phase from the power and charge status registers.
+    ///
+    /// A battery temperature fault is read live from the TS pin ADC and the
+    /// charge temperature window, so it is only detected while the TS pin
+    /// monitors the battery temperature, its ADC channel is enabled and its
+    /// bias current is on outside of charging (`TsPinOutputMode::DuringAdcSampling`
+    /// or `AlwaysEnabled`).
+    #[bisync]
+    pub async fn charger_state(&mut self) -> Result<ChargerState, AxpError<I2CBusErr>> {
+        let mut op = self.ll.power_status();
+        let power = read_internal(&mut op).await?;
+        let mut op = self.ll.charge_status();
+        let charge = read_internal(&mut op).await?;
+        let mut op = self.ll.charge_control_1();
+        let charge_1 = read_internal(&mut op).await?;
+        let battery_temperature_fault = self.battery_outside_charge_window().await?;
+        Ok(ChargerState::decode(
+            power,
+            charge,
+            charge_1.charge_enable(),
+            battery_temperature_fault,
+        ))
+    }
+
+    /// Whether the TS pin voltage is outside the charge temperature window;
+    /// `false` when the TS pin does not measure the battery temperature.
+    #[bisync]
+    async fn battery_outside_charge_window(&mut self) -> Result<bool, AxpError<I2CBusErr>> {
+        let mut op = self.ll.adc_sample_rate_ts_pin_control();
+        let ts_control = read_internal(&mut op).await?;
+        let mut op = self.ll.adc_enable_1();
+        let ts_adc_enabled = read_internal(&mut op).await?.ts_pin_adc_enable();
+        let bias_on = matches!(
+            ts_control.ts_pin_current_output_mode(),
+            TsPinOutputMode::DuringAdcSampling | TsPinOutputMode::AlwaysEnabled
+        );
+        if ts_control.ts_pin_function_is_external_adc() || !ts_adc_enabled || !bias_on {
+            return Ok(false);
+        }
+        let ts_mv = self.get_ts_pin_voltage().await?.0;
+        let mut op = self.ll.battery_charge_low_temp_threshold();
+        let low_temp_raw = read_internal(&mut op).await?.threshold_setting_raw();
+        let mut op = self.ll.battery_charge_high_temp_threshold();
+        let high_temp_raw = read_internal(&mut op).await?.threshold_setting_raw();
+        Ok(ts_mv > temp_threshold_mv_from_raw(low_temp_raw) as u32
+            || ts_mv < temp_threshold_mv_from_raw(high_temp_raw) as u32)
+    }
+
+    /// Programs the power key timings of `PekKeyParameters`.
+    #[bisync]
+    pub async fn set_pek_config(&mut self, pek: &PekConfig) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.pek_key_parameters();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_power_on_time(pek.power_on_time);
+            r.set_long_press_time(pek.long_press_time);
+            r.set_shutdown_time(pek.shutdown_time);
+            r.set_auto_shutdown_if_pek_held_longer_than_shutdown_time(pek.auto_shutdown);
+            r.set_pwrok_signal_delay(pek.pwrok_delay);
+        })
+        .await?;
+        Ok(())
+    }
+
+    #[bisync]
+    pub async fn get_pek_config(&mut self) -> Result<PekConfig, AxpError<I2CBusErr>> {
+        let mut op = self.ll.pek_key_parameters();
+        let pek_params = read_internal(&mut op).await?;
+        Ok(PekConfig {
+            power_on_time: pek_params.power_on_time(),
+            long_press_time: pek_params.long_press_time(),
+            shutdown_time: pek_params.shutdown_time(),
+            auto_shutdown: pek_params.auto_shutdown_if_pek_held_longer_than_shutdown_time(),
+            pwrok_delay: pek_params.pwrok_signal_delay(),
+        })
+    }
+
+    /// Returns and clears a latched power key press, `None` if there is none.
+    ///
+    /// Only the PEK flags are cleared. If both a short and a
+    /// long press are latched, the long press is reported.
+    #[bisync]
+    pub async fn read_pek_press(&mut self) -> Result<Option<PekPress>, AxpError<I2CBusErr>> {
+        let pending = self
+            .read_pending_irqs()
+            .await?
+            .intersection(AxpIrq::PekShortPress | AxpIrq::PekLongPress);
+        if pending.is_empty() {
+            return Ok(None);
+        }
+        self.clear_irqs(pending).await?;
+        if pending.contains(AxpIrq::PekLongPress) {
+            Ok(Some(PekPress::Long))
+        } else {
+            Ok(Some(PekPress::Short))
+        }
+    }
+
+    /// Waits on the AXP192 IRQ pin for the next power key press.
+    ///
+    /// Enables `PekShortPress` and `PekLongPress` on top of the IRQs already
+    /// enabled and only reads and clears the PEK flags. Other sources share the
+    /// pin, so it returns `None` when the pin is pulled low without a pending
+    /// press: the other flags are left for their owner to handle and clear, as
+    /// the pin stays low until they are. Use an
+    /// [`AxpEventStream`](crate::AxpEventStream) to handle every source in one
+    /// place.
+    #[only_async]
+    pub async fn wait_for_press<IrqPin>(
+        &mut self,
+        irq_pin: &mut IrqPin,
+    ) -> Result<Option<PekPress>, AxpError<I2CBusErr>>
+    where
+        IrqPin: embedded_hal_async::digital::Wait,
+    {
+        use embedded_hal::digital::Error as _;
+
+        self.enable_irqs(AxpIrq::PekShortPress | AxpIrq::PekLongPress)
+            .await?;
+        irq_pin
+            .wait_for_low()
+            .await
+            .map_err(|e| AxpError::IrqPin(e.kind()))?;
+        self.read_pek_press().await
+    }
+
+    /// Sets the APS voltage below which the chip shuts down, rounded down to a
+    /// 100mV step. Values outside 2600-3300mV return `InvalidVoltage`.
+    #[bisync]
+    pub async fn set_voff_mv(&mut self, voltage_mv: u16) -> Result<(), AxpError<I2CBusErr>> {
+        let voff =
+            VoffVoltageValue::from_mv(voltage_mv).ok_or(AxpError::InvalidVoltage(voltage_mv))?;
+        let mut op = self.ll.shutdown_voltage_setting();
+        modify_internal(&mut op, |r| r.set_voff_voltage(voff)).await
+    }
+
+    #[bisync]
+    pub async fn get_voff_mv(&mut self) -> Result<u16, AxpError<I2CBusErr>> {
+        let mut op = self.ll.shutdown_voltage_setting();
+        Ok(read_internal(&mut op).await?.voff_voltage().mv())
+    }
+
+    /// Turns every output off until the next power-on event.
+    ///
+    /// The host loses power before this returns on most boards, so an `Ok` is
+    /// only seen when the host has another supply.
+    #[bisync]
+    pub async fn power_off(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.shutdown_bat_chg_led_control();
+        modify_internal(&mut op, |r| r.set_request_shutdown_mode_a(true)).await
+    }
+
+    /// Like [`power_off`](Self::power_off), but first passes the contents of
+    /// `DataBuffer0`-`DataBuffer5` to `save` and writes them back if it changed them.
+    #[bisync]
+    pub async fn power_off_with<F>(&mut self, save: F) -> Result<(), AxpError<I2CBusErr>>
+    where
+        F: FnOnce(&mut [u8; BACKUP_DATA_LEN]),
+    {
+        let old = self.read_backup_data().await?;
+        let mut data = old;
+        save(&mut data);
+        if data != old {
+            self.write_backup_data(&data).await?;
+        }
+        self.power_off().await
+    }
+
+    /// Reads `DataBuffer0`-`DataBuffer5` in one transfer.
+    #[bisync]
+    pub async fn read_backup_data(&mut self) -> Result<[u8; BACKUP_DATA_LEN], AxpError<I2CBusErr>> {
+        let mut data = [0u8; BACKUP_DATA_LEN];
+        self.read_raw_registers(DATA_BUFFER_START, &mut data)
+            .await?;
+        Ok(data)
+    }
+
+    /// Writes `DataBuffer0`-`DataBuffer5` in one transfer.
+    #[bisync]
+    pub async fn write_backup_data(
+        &mut self,
+        data: &[u8; BACKUP_DATA_LEN],
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        self.write_raw_registers(DATA_BUFFER_START, data).await
+    }
+
+    /// Reads the [`BackupRecord`] stored by [`store_backup_record`](Self::store_backup_record).
+    /// The inner `Err` tells reset, corrupted and foreign buffer contents apart.
+    #[bisync]
+    pub async fn load_backup_record<T: BackupRecord>(
+        &mut self,
+    ) -> Result<Result<T, BackupError>, AxpError<I2CBusErr>> {
+        let data = self.read_backup_data().await?;
+        Ok(decode_record(&data))
+    }
+
+    #[bisync]
+    pub async fn store_backup_record<T: BackupRecord>(
+        &mut self,
+        record: &T,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        self.write_backup_data(&encode_record(record)).await
+    }
+
+    /// Puts the chip to sleep as described by `wake`.
+    ///
+    /// The ADCs and the GPIO0 LDO are switched off first, then the rails
+    /// `wake` does not keep. A rail feeding the host must be kept, or the host
+    /// stops here. Re-apply the awake configuration after waking up.
+    #[bisync]
+    pub async fn sleep(&mut self, wake: &WakeConfig) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.shutdown_voltage_setting();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_sleep_pwron_short_press_wakeup_enable(wake.pek_wakeup)
+        })
+        .await?;
+
+        if wake.disable_adcs {
+            let mut op = self.ll.adc_enable_1();
+            modify_if_changed_internal(&mut op, |r| {
+                r.set_battery_voltage_adc_enable(false);
+                r.set_battery_current_adc_enable(false);
+                r.set_acin_voltage_adc_enable(false);
+                r.set_acin_current_adc_enable(false);
+                r.set_vbus_voltage_adc_enable(false);
+                r.set_vbus_current_adc_enable(false);
+                r.set_aps_voltage_adc_enable(false);
+                r.set_ts_pin_adc_enable(false);
+            })
+            .await?;
+            let mut op = self.ll.adc_enable_2();
+            modify_if_changed_internal(&mut op, |r| {
+                r.set_internal_temperature_adc_enable(false);
+                r.set_gpio_0_adc_enable(false);
+                r.set_gpio_1_adc_enable(false);
+                r.set_gpio_2_adc_enable(false);
+                r.set_gpio_3_adc_enable(false);
+            })
+            .await?;
+        }
+
+        if !wake.gpio0_ldo {
+            let mut op = self.ll.gpio_0_control();
+            modify_if_changed_internal(&mut op, |r| {
+                if r.function_select() == Gpio0FunctionSelect::LowNoiseLdoOutput {
+                    r.set_function_select(Gpio0FunctionSelect::Floating);
+                }
+            })
+            .await?;
+        }
+
+        let mut op = self.ll.power_output_control();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_dcdc_1_output_enable(r.dcdc_1_output_enable() && wake.dcdc1);
+            r.set_dcdc_2_output_enable(r.dcdc_2_output_enable() && wake.dcdc2);
+            r.set_dcdc_3_output_enable(r.dcdc_3_output_enable() && wake.dcdc3);
+            r.set_ldo_2_output_enable(r.ldo_2_output_enable() && wake.ldo2);
+            r.set_ldo_3_output_enable(r.ldo_3_output_enable() && wake.ldo3);
+            r.set_exten_output_enable(r.exten_output_enable() && wake.exten);
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Brings the regulators to the state described by `config`.
+    ///
+    /// All values are validated before anything is written, and only registers
+    /// whose contents differ are written back. Voltages are programmed first,
+    /// then DC-DC modes and frequency, then the GPIO0 LDO, and outputs are
+    /// switched on or off last, so a rail never comes up at its old voltage.
+    /// `charger` and `pek` are programmed when `Some`, before the outputs.
+    #[bisync]
+    pub async fn apply_config(&mut self, config: &PowerConfig) -> Result<(), AxpError<I2CBusErr>> {
+        let raw = config.encode()?;
+
+        let mut op = self.ll.dc_dc_1_voltage_setting();
+        modify_if_changed_internal(&mut op, |r| r.set_voltage_setting(raw.dcdc1)).await?;
+        let mut op = self.ll.dc_dc_2_voltage_setting();
+        modify_if_changed_internal(&mut op, |r| r.set_voltage_setting(raw.dcdc2)).await?;
+        let mut op = self.ll.dc_dc_3_voltage_setting();
+        modify_if_changed_internal(&mut op, |r| r.set_voltage_setting(raw.dcdc3)).await?;
+        let mut op = self.ll.ldo_2_and_3_voltage_setting();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_ldo_2_voltage_setting(raw.ldo2);
+            r.set_ldo_3_voltage_setting(raw.ldo3);
+        })
+        .await?;
+
+        let mut op = self.ll.dc_dc_operating_mode();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_dcdc_1_operating_mode(config.dcdc1_mode);
+            r.set_dcdc_2_operating_mode(config.dcdc2_mode);
+            r.set_dcdc_3_operating_mode(config.dcdc3_mode);
+        })
+        .await?;
+        let mut op = self.ll.dc_dc_operating_frequency();
+        modify_if_changed_internal(&mut op, |r| r.set_frequency_setting_raw(raw.frequency)).await?;
+
+        if let (Some(rail), Some(gpio0_raw)) = (config.gpio0_ldo, raw.gpio0_ldo) {
+            let mut op = self.ll.gpio_0_ldo_voltage_setting();
+            modify_if_changed_internal(&mut op, |r| r.set_voltage_setting_raw(gpio0_raw)).await?;
+            let mut op = self.ll.gpio_0_control();
+            modify_if_changed_internal(&mut op, |r| {
+                if rail.enabled {
+                    r.set_function_select(Gpio0FunctionSelect::LowNoiseLdoOutput);
+                } else if r.function_select() == Gpio0FunctionSelect::LowNoiseLdoOutput {
+                    r.set_function_select(Gpio0FunctionSelect::Floating);
+                }
+            })
+            .await?;
+        }
+
+        if let Some(charger) = &config.charger {
+            self.set_charger_config(charger).await?;
+        }
+        if let Some(pek) = &config.pek {
+            self.set_pek_config(pek).await?;
+        }
+
+        let mut op = self.ll.power_output_control();
+        modify_if_changed_internal(&mut op, |r| {
+            r.set_dcdc_1_output_enable(config.dcdc1.enabled);
+            r.set_dcdc_2_output_enable(config.dcdc2.enabled);
+            r.set_dcdc_3_output_enable(config.dcdc3.enabled);
+            r.set_ldo_2_output_enable(config.ldo2.enabled);
+            r.set_ldo_3_output_enable(config.ldo3.enabled);
+            r.set_exten_output_enable(config.exten);
+        })
+        .await?;
+        Ok(())
+    }
+
+    /// Reads the current regulator, charger and power key configuration.
+    ///
+    /// `gpio0_ldo` is `Some` only while GPIO0 is in LDO mode; `charger` and
+    /// `pek` are always `Some`.
+    #[bisync]
+    pub async fn read_config(&mut self) -> Result<PowerConfig, AxpError<I2CBusErr>> {
+        let mut op = self.ll.power_output_control();
+        let outputs = read_internal(&mut op).await?;
+        let mut op = self.ll.dc_dc_1_voltage_setting();
+        let dcdc1_raw = read_internal(&mut op).await?.voltage_setting();
+        let mut op = self.ll.dc_dc_2_voltage_setting();
+        let dcdc2_raw = read_internal(&mut op).await?.voltage_setting();
+        let mut op = self.ll.dc_dc_3_voltage_setting();
+        let dcdc3_raw = read_internal(&mut op).await?.voltage_setting();
+        let mut op = self.ll.ldo_2_and_3_voltage_setting();
+        let ldo = read_internal(&mut op).await?;
+        let mut op = self.ll.dc_dc_operating_mode();
+        let modes = read_internal(&mut op).await?;
+        let mut op = self.ll.dc_dc_operating_frequency();
+        let frequency_raw = read_internal(&mut op).await?.frequency_setting_raw();
+
+        let mut op = self.ll.gpio_0_control();
+        let gpio0_function = read_internal(&mut op).await?.function_select();
+        let gpio0_ldo = if gpio0_function == Gpio0FunctionSelect::LowNoiseLdoOutput {
+            let mut op = self.ll.gpio_0_ldo_voltage_setting();
+            let raw = read_internal(&mut op).await?.voltage_setting_raw();
+            Some(RailConfig::on(config::ldo_mv_from_raw(raw)))
+        } else {
+            None
+        };
+
+        let charger = self.get_charger_config().await?;
+
+        let pek = self.get_pek_config().await?;
+
+        let rail = |enabled: bool, voltage_mv: u16| RailConfig {
+            enabled,
+            voltage_mv,
+        };
+        Ok(PowerConfig {
+            dcdc1: rail(
+                outputs.dcdc_1_output_enable(),
+                config::dcdc_mv_from_raw(dcdc1_raw),
+            ),
+            dcdc2: rail(
+                outputs.dcdc_2_output_enable(),
+                config::dcdc_mv_from_raw(dcdc2_raw),
+            ),
+            dcdc3: rail(
+                outputs.dcdc_3_output_enable(),
+                config::dcdc_mv_from_raw(dcdc3_raw),
+            ),
+            ldo2: rail(
+                outputs.ldo_2_output_enable(),
+                config::ldo_mv_from_raw(ldo.ldo_2_voltage_setting()),
+            ),
+            ldo3: rail(
+                outputs.ldo_3_output_enable(),
+                config::ldo_mv_from_raw(ldo.ldo_3_voltage_setting()),
+            ),
+            gpio0_ldo,
+            exten: outputs.exten_output_enable(),
+            dcdc1_mode: modes.dcdc_1_operating_mode(),
+            dcdc2_mode: modes.dcdc_2_operating_mode(),
+            dcdc3_mode: modes.dcdc_3_operating_mode(),
+            dcdc_frequency_khz: config::frequency_khz_from_raw(frequency_raw),
+            charger: Some(charger),
+            pek: Some(pek),
+        })
+    }
+
+    /// Applies the power profile of `board` and sets up its AXP192-driven GPIOs.
+    ///
+    /// All ADC channels of `AdcEnable1` are switched on. On boards with those
+    /// lines, the power LED is lit, the speaker amplifier is left disabled and the
+    /// LCD reset line is released.
+    #[bisync]
+    pub async fn init_for_board(&mut self, board: Board) -> Result<(), AxpError<I2CBusErr>> {
+        self.apply_config(&board.power_config()).await?;
+
+        let mut op = self.ll.adc_enable_1();
+        write_internal(&mut op, |r| {
+            r.set_battery_voltage_adc_enable(true);
+            r.set_battery_current_adc_enable(true);
+            r.set_acin_voltage_adc_enable(true);
+            r.set_acin_current_adc_enable(true);
+            r.set_vbus_voltage_adc_enable(true);
+            r.set_vbus_current_adc_enable(true);
+            r.set_aps_voltage_adc_enable(true);
+            r.set_ts_pin_adc_enable(true);
+        })
+        .await?;
+
+        // Output levels are set before the pins are switched to open-drain mode.
+        if board.has_power_led() {
+            self.set_power_led(board, true).await?;
+            let mut op = self.ll.gpio_1_control();
+            modify_internal(&mut op, |r| {
+                r.set_function_select(Gpio1FunctionSelect::NmosOpenDrainOutput)
+            })
+            .await?;
+        }
+        if board.has_speaker_enable() {
+            self.set_speaker_enable(board, false).await?;
+            let mut op = self.ll.gpio_2_control();
+            modify_internal(&mut op, |r| {
+                r.set_function_select(Gpio2FunctionSelect::NmosOpenDrainOutput)
+            })
+            .await?;
+        }
+        if board.has_lcd_reset() {
+            let mut op = self.ll.gpio_3_and_4_signal_status_and_control();
+            modify_internal(&mut op, |r| r.set_gpio_4_output_set_floating(true)).await?;
+            let mut op = self.ll.gpio_3_and_4_function_control();
+            modify_internal(&mut op, |r| {
+                r.set_gpio_3_and_4_mode_enable(true);
+                r.set_gpio_4_function_select(Gpio4FunctionSetting::NmosOpenDrainOutput);
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Sets the LCD backlight brightness by scaling the backlight rail between
+    /// 2.5V and 3.3V. 0% switches the rail off; values above 100 are clamped.
+    #[bisync]
+    pub async fn set_backlight_percent(
+        &mut self,
+        board: Board,
+        percent: u8,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let rail = board.backlight_rail();
+        if percent == 0 {
+            return self.set_rail_enable(rail, false).await;
+        }
+        let span_mv = BACKLIGHT_MAX_MV - BACKLIGHT_MIN_MV;
+        let voltage_mv = BACKLIGHT_MIN_MV + span_mv * percent.min(100) as u16 / 100;
+        match rail {
+            Rail::Dcdc(dc) => self.set_dcdc_voltage(dc, voltage_mv).await?,
+            Rail::Ldo(ldo) => self.set_ldo_voltage_mv(ldo, voltage_mv).await?,
+        }
+        self.set_rail_enable(rail, true).await
+    }
+
+    #[bisync]
+    pub async fn set_vibration(
+        &mut self,
+        board: Board,
+        on: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let rail = board
+            .vibration_rail()
+            .ok_or(AxpError::NotSupported("board has no vibration motor"))?;
+        self.set_rail_enable(rail, on).await
+    }
+
+    #[bisync]
+    pub async fn set_speaker_enable(
+        &mut self,
+        board: Board,
+        enable: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        if !board.has_speaker_enable() {
+            return Err(AxpError::NotSupported("board has no AXP192 speaker enable"));
+        }
+        let mut op = self.ll.gpio_0_to_2_signal_status_and_control();
+        // The amplifier enable is pulled up, so releasing the open-drain output enables it.
+        modify_internal(&mut op, |r| r.set_gpio_2_output_set_floating(enable)).await
+    }
+
+    #[bisync]
+    pub async fn set_power_led(
+        &mut self,
+        board: Board,
+        on: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        if !board.has_power_led() {
+            return Err(AxpError::NotSupported("board has no AXP192 power LED"));
+        }
+        let mut op = self.ll.gpio_0_to_2_signal_status_and_control();
+        // The LED is lit while the open-drain output pulls low.
+        modify_internal(&mut op, |r| r.set_gpio_1_output_set_floating(!on)).await
+    }
+
+    #[bisync]
+    async fn set_rail_enable(
+        &mut self,
+        rail: Rail,
+        enable: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        match rail {
+            Rail::Dcdc(dc) => self.set_dcdc_enable(dc, enable).await,
+            Rail::Ldo(ldo) => self.set_ldo_enable(ldo, enable).await,
+        }
+    }
+
+    /// Switches a GPIO to an open-drain output or an input.
+    ///
+    /// GPIO3 and GPIO4 share a GPIO mode enable bit, so configuring either one
+    /// also takes the other out of its external charge control function.
+    #[bisync]
+    pub async fn set_gpio_mode(
+        &mut self,
+        gpio: GpioId,
+        mode: GpioMode,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let output = mode == GpioMode::OpenDrainOutput;
+        match gpio {
+            GpioId::Gpio0 => {
+                let mut op = self.ll.gpio_0_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(if output {
+                        Gpio0FunctionSelect::NmosOpenDrainOutput
+                    } else {
+                        Gpio0FunctionSelect::UniversalInput
+                    })
+                })
+                .await
+            }
+            GpioId::Gpio1 => {
+                let mut op = self.ll.gpio_1_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(if output {
+                        Gpio1FunctionSelect::NmosOpenDrainOutput
+                    } else {
+                        Gpio1FunctionSelect::UniversalInput
+                    })
+                })
+                .await
+            }
+            GpioId::Gpio2 => {
+                let mut op = self.ll.gpio_2_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(if output {
+                        Gpio2FunctionSelect::NmosOpenDrainOutput
+                    } else {
+                        Gpio2FunctionSelect::UniversalInput
+                    })
+                })
+                .await
+            }
+            GpioId::Gpio3 => {
+                let mut op = self.ll.gpio_3_and_4_function_control();
+                modify_internal(&mut op, |r| {
+                    r.set_gpio_3_and_4_mode_enable(true);
+                    r.set_gpio_3_function_select(if output {
+                        Gpio3FunctionSetting::NmosOpenDrainOutput
+                    } else {
+                        Gpio3FunctionSetting::UniversalInput
+                    });
+                })
+                .await
+            }
+            GpioId::Gpio4 => {
+                let mut op = self.ll.gpio_3_and_4_function_control();
+                modify_internal(&mut op, |r| {
+                    r.set_gpio_3_and_4_mode_enable(true);
+                    r.set_gpio_4_function_select(if output {
+                        Gpio4FunctionSetting::NmosOpenDrainOutput
+                    } else {
+                        Gpio4FunctionSetting::UniversalInput
+                    });
+                })
+                .await
+            }
+            GpioId::Gpio5 => {
+                let mut op = self.ll.nrsto_gpio_5_control();
+                modify_internal(&mut op, |r| {
+                    r.set_pin_is_gpio_5(NrstoPinFunction::Gpio5);
+                    r.set_gpio_5_is_input(if output {
+                        Gpio5Direction::NmosOpenDrainOutput
+                    } else {
+                        Gpio5Direction::UniversalInput
+                    });
+                })
+                .await
+            }
+        }
+    }
+
+    /// Releases an open-drain GPIO output (`high`) or pulls it low.
+    #[bisync]
+    pub async fn set_gpio_output(
+        &mut self,
+        gpio: GpioId,
+        high: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        match gpio {
+            GpioId::Gpio0 | GpioId::Gpio1 | GpioId::Gpio2 => {
+                let mut op = self.ll.gpio_0_to_2_signal_status_and_control();
+                modify_internal(&mut op, |r| match gpio {
+                    GpioId::Gpio0 => r.set_gpio_0_output_set_floating(high),
+                    GpioId::Gpio1 => r.set_gpio_1_output_set_floating(high),
+                    _ => r.set_gpio_2_output_set_floating(high),
+                })
+                .await
+            }
+            GpioId::Gpio3 | GpioId::Gpio4 => {
+                let mut op = self.ll.gpio_3_and_4_signal_status_and_control();
+                modify_internal(&mut op, |r| match gpio {
+                    GpioId::Gpio3 => r.set_gpio_3_output_set_floating(high),
+                    _ => r.set_gpio_4_output_set_floating(high),
+                })
+                .await
+            }
+            GpioId::Gpio5 => {
+                let mut op = self.ll.nrsto_gpio_5_control();
+                modify_internal(&mut op, |r| r.set_gpio_5_output_set_floating(high)).await
+            }
+        }
+    }
+
+    /// The output level last written with [`Self::set_gpio_output`].
+    #[bisync]
+    pub async fn get_gpio_output(&mut self, gpio: GpioId) -> Result<bool, AxpError<I2CBusErr>> {
+        match gpio {
+            GpioId::Gpio0 | GpioId::Gpio1 | GpioId::Gpio2 => {
+                let mut op = self.ll.gpio_0_to_2_signal_status_and_control();
+                let status = read_internal(&mut op).await?;
+                Ok(match gpio {
+                    GpioId::Gpio0 => status.gpio_0_output_set_floating(),
+                    GpioId::Gpio1 => status.gpio_1_output_set_floating(),
+                    _ => status.gpio_2_output_set_floating(),
+                })
+            }
+            GpioId::Gpio3 | GpioId::Gpio4 => {
+                let mut op = self.ll.gpio_3_and_4_signal_status_and_control();
+                let status = read_internal(&mut op).await?;
+                Ok(match gpio {
+                    GpioId::Gpio3 => status.gpio_3_output_set_floating(),
+                    _ => status.gpio_4_output_set_floating(),
+                })
+            }
+            GpioId::Gpio5 => {
+                let mut op = self.ll.nrsto_gpio_5_control();
+                Ok(read_internal(&mut op).await?.gpio_5_output_set_floating())
+            }
+        }
+    }
+
+    /// The level sensed on a GPIO.
+    #[bisync]
+    pub async fn get_gpio_input(&mut self, gpio: GpioId) -> Result<bool, AxpError<I2CBusErr>> {
+        match gpio {
+            GpioId::Gpio0 | GpioId::Gpio1 | GpioId::Gpio2 => {
+                let mut op = self.ll.gpio_0_to_2_signal_status_and_control();
+                let status = read_internal(&mut op).await?;
+                Ok(match gpio {
+                    GpioId::Gpio0 => status.gpio_0_input_status(),
+                    GpioId::Gpio1 => status.gpio_1_input_status(),
+                    _ => status.gpio_2_input_status(),
+                })
+            }
+            GpioId::Gpio3 | GpioId::Gpio4 => {
+                let mut op = self.ll.gpio_3_and_4_signal_status_and_control();
+                let status = read_internal(&mut op).await?;
+                Ok(match gpio {
+                    GpioId::Gpio3 => status.gpio_3_input_status(),
+                    _ => status.gpio_4_input_status(),
+                })
+            }
+            GpioId::Gpio5 => {
+                let mut op = self.ll.nrsto_gpio_5_control();
+                Ok(read_internal(&mut op).await?.gpio_5_input_status())
+            }
+        }
+    }
+
+    /// Switches GPIO1 or GPIO2 to the output of its PWM generator.
+    #[bisync]
+    pub async fn enable_pwm_output(&mut self, pwm: PwmId) -> Result<(), AxpError<I2CBusErr>> {
+        match pwm {
+            PwmId::Pwm1 => {
+                let mut op = self.ll.gpio_1_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(Gpio1FunctionSelect::Pwm1Output)
+                })
+                .await
+            }
+            PwmId::Pwm2 => {
+                let mut op = self.ll.gpio_2_control();
+                modify_internal(&mut op, |r| {
+                    r.set_function_select(Gpio2FunctionSelect::Pwm2Output)
+                })
+                .await
+            }
+        }
+    }
+
+    #[bisync]
+    pub async fn get_pwm_parameters(
+        &mut self,
+        pwm: PwmId,
+    ) -> Result<PwmParameters, AxpError<I2CBusErr>> {
+        let mut raw = [0u8; 3];
+        self.read_raw_registers(pwm.base_address(), &mut raw)
+            .await?;
+        Ok(PwmParameters {
+            x: raw[0],
+            y1: raw[1],
+            y2: field_sets::Pwm1DutyCycleSettingY2::from([raw[2]]).parameter_y_2(),
+        })
+    }
+
+    /// Writes X, Y1 and Y2 in one burst. Y2 above 31 is clamped.
+    #[bisync]
+    pub async fn set_pwm_parameters(
+        &mut self,
+        pwm: PwmId,
+        parameters: &PwmParameters,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let mut y2 = field_sets::Pwm1DutyCycleSettingY2::new_zero();
+        y2.set_parameter_y_2(parameters.y2.min(PWM_Y2_MAX));
+        let [y2] = y2.into();
+        self.write_raw_registers(pwm.base_address(), &[parameters.x, parameters.y1, y2])
+            .await
+    }
+
+    /// Sets the PWM frequency to the closest one reachable, keeping the duty
+    /// cycle, and returns the frequency set.
+    #[bisync]
+    pub async fn set_pwm_frequency_hz(
+        &mut self,
+        pwm: PwmId,
+        frequency_hz: u32,
+    ) -> Result<u32, AxpError<I2CBusErr>> {
+        let (x, y1) =
+            solve_frequency(frequency_hz).ok_or(AxpError::InvalidPwmFrequency(frequency_hz))?;
+        let old = self.get_pwm_parameters(pwm).await?;
+        let y2 = if old.y1 == 0 {
+            0
+        } else {
+            let scaled =
+                (old.y2.min(old.y1) as u16 * y1 as u16 + old.y1 as u16 / 2) / old.y1 as u16;
+            scaled as u8
+        };
+        let parameters = PwmParameters { x, y1, y2 };
+        self.set_pwm_parameters(pwm, &parameters).await?;
+        Ok(parameters.frequency_hz())
+    }
+
+    /// Sets Y2, the high time out of Y1 counts. Values above 31 are clamped.
+    #[bisync]
+    pub async fn set_pwm_duty(&mut self, pwm: PwmId, y2: u8) -> Result<(), AxpError<I2CBusErr>> {
+        let mut field = field_sets::Pwm1DutyCycleSettingY2::new_zero();
+        field.set_parameter_y_2(y2.min(PWM_Y2_MAX));
+        let [raw] = field.into();
+        self.write_raw_registers(pwm.base_address() + 2, &[raw])
+            .await
+    }
+
+    #[bisync]
+    pub async fn read_enabled_irqs(&mut self) -> Result<AxpIrqSet, AxpError<I2CBusErr>> {
+        let mut banks = [0u8; 5];
+        for (bank, address) in banks.iter_mut().zip(IRQ_ENABLE_ADDRESSES) {
+            self.read_raw_registers(address, core::slice::from_mut(bank))
+                .await?;
+        }
+        Ok(AxpIrqSet::from_banks(banks))
+    }
+
+    /// Enables exactly the given IRQ sources and disables all others.
+    #[bisync]
+    pub async fn set_enabled_irqs(&mut self, irqs: AxpIrqSet) -> Result<(), AxpError<I2CBusErr>> {
+        for (bank, address) in irqs.banks().into_iter().zip(IRQ_ENABLE_ADDRESSES) {
+            self.write_raw_registers(address, &[bank]).await?;
+        }
+        Ok(())
+    }
+
+    /// Enables the given IRQ sources, leaving the others untouched.
+    #[bisync]
+    pub async fn enable_irqs(&mut self, irqs: AxpIrqSet) -> Result<(), AxpError<I2CBusErr>> {
+        let enabled = self.read_enabled_irqs().await?;
+        self.set_enabled_irqs(enabled | irqs).await
+    }
+
+    /// Disables the given IRQ sources, leaving the others untouched.
+    #[bisync]
+    pub async fn disable_irqs(&mut self, irqs: AxpIrqSet) -> Result<(), AxpError<I2CBusErr>> {
+        let enabled = self.read_enabled_irqs().await?;
+        self.set_enabled_irqs(enabled.difference(irqs)).await
+    }
+
+    /// Reads `IrqStatus1`-`IrqStatus5`. Status bits are reported whether or not
+    /// the source is enabled; mask with [`Self::read_enabled_irqs`] if needed.
+    #[bisync]
+    pub async fn read_pending_irqs(&mut self) -> Result<AxpIrqSet, AxpError<I2CBusErr>> {
+        // IrqStatus1-4 are contiguous and can be read in one burst, IrqStatus5 sits apart.
+        let mut banks = [0u8; 5];
+        self.read_raw_registers(IRQ_STATUS_ADDRESSES[0], &mut banks[..4])
+            .await?;
+        self.read_raw_registers(IRQ_STATUS_ADDRESSES[4], &mut banks[4..])
+            .await?;
+        Ok(AxpIrqSet::from_banks(banks))
+    }
+
+    /// Clears the given IRQ status flags. Status registers are write-1-to-clear,
+    /// so only the requested bits are written and other pending flags survive.
+    #[bisync]
+    pub async fn clear_irqs(&mut self, irqs: AxpIrqSet) -> Result<(), AxpError<I2CBusErr>> {
+        let banks = irqs.banks();
+        if banks[..4].iter().any(|bank| *bank != 0) {
+            self.write_raw_registers(IRQ_STATUS_ADDRESSES[0], &banks[..4])
+                .await?;
+        }
+        if banks[4] != 0 {
+            self.write_raw_registers(IRQ_STATUS_ADDRESSES[4], &banks[4..])
+                .await?;
+        }
+        Ok(())
+    }
+
+    #[bisync]
+    pub async fn set_coulomb_counter_enable(
+        &mut self,
+        enable: bool,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.coulomb_counter_control();
+        modify_internal(&mut op, |r| r.set_coulomb_counter_enable(enable)).await
+    }
+
+    /// Pauses the coulomb counter. Counting resumes with `set_coulomb_counter_enable(true)`.
+    #[bisync]
+    pub async fn pause_coulomb_counter(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.coulomb_counter_control();
+        modify_internal(&mut op, |r| r.set_coulomb_counter_pause_request(true)).await
+    }
+
+    /// Resets both coulomb counters to zero. Any [`SocEstimator`] fed from them
+    /// should be [`reset`](SocEstimator::reset) afterwards.
+    #[bisync]
+    pub async fn clear_coulomb_counter(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        let mut op = self.ll.coulomb_counter_control();
+        modify_internal(&mut op, |r| r.set_coulomb_counter_clear_request(true)).await
+    }
+
+    #[bisync]
+    pub async fn read_coulomb_counters(&mut self) -> Result<CoulombCounts, AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_charge_coulomb_counter();
+        let charge = read_internal(&mut op).await?.raw();
+        let mut op = self.ll.battery_discharge_coulomb_counter();
+        let discharge = read_internal(&mut op).await?.raw();
+        Ok(CoulombCounts { charge, discharge })
+    }
+
+    #[bisync]
+    pub async fn get_adc_sample_rate(&mut self) -> Result<AdcSampleRateValue, AxpError<I2CBusErr>> {
+        let mut op = self.ll.adc_sample_rate_ts_pin_control();
+        Ok(read_internal(&mut op).await?.adc_sample_rate())
+    }
+
+    /// Net charge counted since the last clear (positive when charging dominated).
+    #[bisync]
+    pub async fn get_coulomb_net_charge(&mut self) -> Result<MicroampHours, AxpError<I2CBusErr>> {
+        let counts = self.read_coulomb_counters().await?;
+        let sample_rate = self.get_adc_sample_rate().await?;
+        Ok(MicroampHours(counts.net_uah(sample_rate)))
+    }
+
+    /// Net charge counted since the last clear, in mAh (positive when charging dominated).
+    #[cfg(feature = "float")]
+    #[bisync]
+    pub async fn get_coulomb_net_charge_mah(&mut self) -> Result<f32, AxpError<I2CBusErr>> {
+        let counts = self.read_coulomb_counters().await?;
+        let sample_rate = self.get_adc_sample_rate().await?;
+        Ok(counts.net_mah(sample_rate))
+    }
+
+    /// Samples battery voltage, current and coulomb counters and updates `estimator`.
+    /// Returns the state of charge in percent.
+    #[bisync]
+    pub async fn update_soc_estimate(
+        &mut self,
+        estimator: &mut SocEstimator,
+    ) -> Result<u8, AxpError<I2CBusErr>> {
+        let (battery_mv, battery_current_ma) = self.read_battery_sample().await?;
+        let counts = self.read_coulomb_counters().await?;
+        let sample_rate = self.get_adc_sample_rate().await?;
+        Ok(estimator.update(battery_mv, battery_current_ma, counts, sample_rate))
+    }
+
+    /// State of charge in percent from the IR-compensated battery voltage alone.
+    #[bisync]
+    pub async fn estimate_soc_from_voltage(
+        &mut self,
+        estimator: &VoltageSocEstimator,
+    ) -> Result<u8, AxpError<I2CBusErr>> {
+        let (battery_mv, battery_current_ma) = self.read_battery_sample().await?;
+        Ok(estimator.soc_percent(battery_mv, battery_current_ma))
+    }
+
+    /// Battery voltage in mV and net battery current in mA (positive while charging).
+    #[bisync]
+    async fn read_battery_sample(&mut self) -> Result<(u16, i32), AxpError<I2CBusErr>> {
+        let mut op = self.ll.battery_voltage_adc();
+        let voltage_adc = adc_12bit_from_raw_u16(read_internal(&mut op).await?.raw());
+        let mut op = self.ll.battery_charge_current_adc();
+        let charge_adc = adc_13bit_from_raw_u16(read_internal(&mut op).await?.raw());
+        let mut op = self.ll.battery_discharge_current_adc();
+        let discharge_adc = adc_13bit_from_raw_u16(read_internal(&mut op).await?.raw());
+        let battery_mv = battery_voltage_from_adc(voltage_adc).mv() as u16;
+        let battery_current_ma = Microamps(
+            battery_current_from_adc(charge_adc).ua()
+                - battery_current_from_adc(discharge_adc).ua(),
+        )
+        .ma();
+        Ok((battery_mv, battery_current_ma))
+    }
+
+    /// Reads every register defined in `device.yaml`, one transfer per register.
+    ///
+    /// Print the result with `{}` (or defmt `{}`) to log every field, one per line.
+    #[bisync]
+    pub async fn dump_registers(&mut self) -> Result<RegisterDump, AxpError<I2CBusErr>> {
+        let mut values = [(0u8, 0u32); REGISTER_COUNT];
+        for (entry, info) in values.iter_mut().zip(REGISTERS.iter()) {
+            let mut bytes = [0u8; 4];
+            let size = info.size_bytes as usize;
+            self.read_raw_registers(info.address, &mut bytes[4 - size..])
+                .await?;
+            *entry = (info.address, u32::from_be_bytes(bytes));
+        }
+        Ok(RegisterDump(values))
+    }
+
+    /// Reads the configuration registers into a [`RegisterSnapshot`].
+    #[bisync]
+    pub async fn save_snapshot(&mut self) -> Result<RegisterSnapshot, AxpError<I2CBusErr>> {
+        let mut values = [0u8; SNAPSHOT_REGISTER_COUNT];
+        for (value, info) in values.iter_mut().zip(SNAPSHOT_REGISTERS.iter()) {
+            self.read_raw_registers(info.address, core::slice::from_mut(value))
+                .await?;
+        }
+        Ok(RegisterSnapshot::from_values(values))
+    }
+
+    /// Writes `snapshot` back to the chip.
+    ///
+    /// Only registers that differ from the current state are written, in
+    /// address order except for the output enables (`ExtenDcDc2Control`,
+    /// `PowerOutputControl`), which go last so rails come up at their restored
+    /// voltages.
+    #[bisync]
+    pub async fn restore_snapshot(
+        &mut self,
+        snapshot: &RegisterSnapshot,
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let current = self.save_snapshot().await?;
+        let is_output_enable = |address: u8| matches!(address, 0x10 | 0x12);
+        for outputs_pass in [false, true] {
+            let registers = SNAPSHOT_REGISTERS
+                .iter()
+                .zip(current.iter().zip(snapshot.iter()));
+            for (info, ((_, old), (_, new))) in registers {
+                let changed = (old ^ new) & !info.read_only_mask != 0;
+                if changed && is_output_enable(info.address) == outputs_pass {
+                    self.write_raw_registers(info.address, &[new]).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads `data.len()` consecutive registers starting at `address`.
+    #[bisync]
+    async fn read_raw_registers(
+        &mut self,
+        address: u8,
+        data: &mut [u8],
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let size_bits = data.len() as u32 * 8;
+        self.ll
+            .interface()
+            .read_register(address, size_bits, data)
+            .await
+    }
+
+    /// Writes `data` to consecutive registers starting at `address`.
+    #[bisync]
+    async fn write_raw_registers(
+        &mut self,
+        address: u8,
+        data: &[u8],
+    ) -> Result<(), AxpError<I2CBusErr>> {
+        let size_bits = data.len() as u32 * 8;
+        self.ll
+            .interface()
+            .write_register(address, size_bits, data)
+            .await
+    }
 }
@@ -0,0 +1,97 @@
+//! Interrupt-driven event stream for [`Axp192Async`].
+//!
+//! The AXP192 pulls its open-drain IRQ line low for as long as any enabled
+//! status flag is set. [`AxpEventStream`] waits on that line, reads all five
+//! `IrqStatus` registers in one go, clears exactly the enabled flags it read
+//! and hands them out one by one, so several events raised together are never
+//! lost and no status polling is needed while the line is idle. Flags of
+//! sources that were disabled after latching are neither reported nor cleared.
+
+use embedded_hal::digital::Error as _;
+use embedded_hal_async::digital::Wait;
+
+use crate::asynchronous::CurrentAxpDriverInterface;
+use crate::{Axp192Async, AxpError, AxpIrq, AxpIrqSet};
+
+/// A decoded AXP192 event, i.e. an interrupt source whose status flag was set.
+pub type AxpEvent = AxpIrq;
+
+/// Yields [`AxpEvent`]s as the AXP192 raises them on its IRQ pin.
+pub struct AxpEventStream<I2CImpl, I2CBusErr, IrqPin>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    axp: Axp192Async<I2CImpl, I2CBusErr>,
+    irq_pin: IrqPin,
+    queued: AxpIrqSet,
+    /// Enabled sources, read on first use and dropped whenever the driver is
+    /// handed out.
+    enabled: Option<AxpIrqSet>,
+}
+
+impl<I2CImpl, I2CBusErr, IrqPin> AxpEventStream<I2CImpl, I2CBusErr, IrqPin>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+    IrqPin: Wait,
+{
+    /// Wraps the driver and the input pin wired to the AXP192 IRQ output.
+    ///
+    /// Only sources enabled with [`Axp192Async::enable_irqs`] drive the pin.
+    pub fn new(axp: Axp192Async<I2CImpl, I2CBusErr>, irq_pin: IrqPin) -> Self {
+        Self {
+            axp,
+            irq_pin,
+            queued: AxpIrqSet::empty(),
+            enabled: None,
+        }
+    }
+
+    /// Waits for and returns the next event.
+    ///
+    /// Events that were raised together are queued and returned by subsequent
+    /// calls, in register order, before the IRQ pin is waited on again.
+    pub async fn next_event(&mut self) -> Result<AxpEvent, AxpError<I2CBusErr>> {
+        loop {
+            if let Some(event) = self.queued.pop() {
+                return Ok(event);
+            }
+            self.irq_pin
+                .wait_for_low()
+                .await
+                .map_err(|e| AxpError::IrqPin(e.kind()))?;
+            let enabled = match self.enabled {
+                Some(enabled) => enabled,
+                None => *self.enabled.insert(self.axp.read_enabled_irqs().await?),
+            };
+            let fired = self.axp.read_pending_irqs().await?.intersection(enabled);
+            if fired.is_empty() {
+                // Pulled low by something else sharing the line.
+                self.irq_pin
+                    .wait_for_high()
+                    .await
+                    .map_err(|e| AxpError::IrqPin(e.kind()))?;
+                continue;
+            }
+            self.axp.clear_irqs(fired).await?;
+            self.queued |= fired;
+        }
+    }
+
+    /// Events already read from the chip but not yet returned by [`Self::next_event`].
+    pub fn queued(&self) -> AxpIrqSet {
+        self.queued
+    }
+
+    /// Access to the wrapped driver, e.g. to change the enabled IRQ sources.
+    pub fn driver(&mut self) -> &mut Axp192Async<I2CImpl, I2CBusErr> {
+        self.enabled = None;
+        &mut self.axp
+    }
+
+    /// Releases the driver and the IRQ pin.
+    pub fn release(self) -> (Axp192Async<I2CImpl, I2CBusErr>, IrqPin) {
+        (self.axp, self.irq_pin)
+    }
+}
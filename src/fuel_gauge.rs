@@ -0,0 +1,147 @@
+//! Battery fuel gauge built on the AXP192 coulomb counters.
+//!
+//! The chip integrates battery charge and discharge current into two 32-bit
+//! counters (`BatteryChargeCoulombCounter`, REGB0H, and
+//! `BatteryDischargeCoulombCounter`, REGB4H) controlled by
+//! `CoulombCounterControl` (REGB8H). Their scale depends on the ADC sample rate
+//! from `AdcSampleRateTsPinControl`:
+//!
+//! `C (mAh) = 65536 * 0.5mA * (charge - discharge) / 3600 / sample_rate_hz`
+//!
+//! [`SocEstimator`] fuses that net charge with the battery voltage to produce a
+//! state-of-charge percentage.
+
+use crate::{AdcSampleRateValue, VoltageSocEstimator};
+
+impl AdcSampleRateValue {
+    /// The sample rate in Hz.
+    pub const fn hz(self) -> u16 {
+        match self {
+            AdcSampleRateValue::Hz25 => 25,
+            AdcSampleRateValue::Hz50 => 50,
+            AdcSampleRateValue::Hz100 => 100,
+            AdcSampleRateValue::Hz200 => 200,
+        }
+    }
+}
+
+/// Raw contents of the charge and discharge coulomb counters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CoulombCounts {
+    pub charge: u32,
+    pub discharge: u32,
+}
+
+impl CoulombCounts {
+    /// Net counts, positive when more charge went into the battery than out of it.
+    pub fn net(&self) -> i64 {
+        self.charge as i64 - self.discharge as i64
+    }
+
+    /// Net charge in uAh for counters accumulated at `sample_rate`.
+    pub fn net_uah(&self, sample_rate: AdcSampleRateValue) -> i64 {
+        counts_to_uah(self.net(), sample_rate)
+    }
+
+    /// Net charge in mAh for counters accumulated at `sample_rate`.
+    #[cfg(feature = "float")]
+    pub fn net_mah(&self, sample_rate: AdcSampleRateValue) -> f32 {
+        self.net() as f32 * 32768.0 / 3600.0 / sample_rate.hz() as f32
+    }
+}
+
+fn counts_to_uah(counts: i64, sample_rate: AdcSampleRateValue) -> i64 {
+    // 65536 * 0.5mA = 32768mA per count, converted from mAs to uAh.
+    counts * 32_768_000 / (3600 * sample_rate.hz() as i64)
+}
+
+/// State-of-charge estimator combining coulomb counting with battery voltage.
+///
+/// The first sample anchors the estimate to the voltage-derived SoC (see
+/// [`VoltageSocEstimator`]). After that, the net charge reported by the coulomb
+/// counters moves the estimate, while each sample pulls it slightly towards the
+/// voltage-derived value to correct drift and capacity errors.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SocEstimator {
+    capacity_mah: u32,
+    voltage: VoltageSocEstimator,
+    /// Weight of the voltage estimate per update, in 1/256ths.
+    voltage_weight: u8,
+    soc_permille: Option<i32>,
+    last_net_uah: i64,
+}
+
+impl SocEstimator {
+    /// Creates an estimator for a battery of the given nominal capacity, using
+    /// the 4.2V LiPo curve without IR compensation for the voltage part.
+    pub const fn new(capacity_mah: u32) -> Self {
+        Self {
+            capacity_mah,
+            voltage: VoltageSocEstimator::new(crate::OcvCurve::LIPO_4V20),
+            voltage_weight: 4,
+            soc_permille: None,
+            last_net_uah: 0,
+        }
+    }
+
+    /// Replaces the voltage-to-SoC mapping (OCV curve and internal resistance).
+    pub const fn with_voltage_estimator(mut self, voltage: VoltageSocEstimator) -> Self {
+        self.voltage = voltage;
+        self
+    }
+
+    /// Sets how strongly each update trusts the voltage over the coulomb
+    /// counters, in 1/256ths (0 = pure coulomb counting, 255 = almost voltage only).
+    pub const fn with_voltage_weight(mut self, weight: u8) -> Self {
+        self.voltage_weight = weight;
+        self
+    }
+
+    pub const fn capacity_mah(&self) -> u32 {
+        self.capacity_mah
+    }
+
+    /// Forgets the current estimate; the next update re-anchors to the voltage.
+    pub fn reset(&mut self) {
+        self.soc_permille = None;
+    }
+
+    /// Forces the estimate to 100%, e.g. on a charge-done interrupt.
+    pub fn calibrate_full(&mut self) {
+        self.soc_permille = Some(1000);
+    }
+
+    /// Current estimate in percent, if at least one update happened.
+    pub fn soc_percent(&self) -> Option<u8> {
+        self.soc_permille.map(|p| ((p + 5) / 10) as u8)
+    }
+
+    /// Feeds a new sample and returns the updated estimate in percent.
+    /// `battery_current_ma` is positive while charging and negative while discharging.
+    pub fn update(
+        &mut self,
+        battery_mv: u16,
+        battery_current_ma: i32,
+        counts: CoulombCounts,
+        sample_rate: AdcSampleRateValue,
+    ) -> u8 {
+        let net_uah = counts.net_uah(sample_rate);
+        let voltage_soc = self.voltage.soc_permille(battery_mv, battery_current_ma) as i32;
+        let soc = match self.soc_permille {
+            None => voltage_soc,
+            Some(previous) => {
+                let delta_uah = net_uah - self.last_net_uah;
+                let capacity_uah = (self.capacity_mah as i64 * 1000).max(1);
+                let coulomb_soc = previous + (delta_uah * 1000 / capacity_uah) as i32;
+                let weight = self.voltage_weight as i32;
+                (coulomb_soc * (256 - weight) + voltage_soc * weight) / 256
+            }
+        };
+        let soc = soc.clamp(0, 1000);
+        self.soc_permille = Some(soc);
+        self.last_net_uah = net_uah;
+        ((soc + 5) / 10) as u8
+    }
+}
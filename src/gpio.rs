@@ -0,0 +1,50 @@
+//! GPIO0-GPIO5 as digital pins.
+//!
+//! Each GPIO is configured through a different register: GPIO0-2 have one
+//! function register each (REG90H, REG92H, REG93H) and share REG94H for levels,
+//! GPIO3 and GPIO4 share REG95H and REG96H, and GPIO5 is the N_RSTO pin in
+//! REG9EH. `Axp192::set_gpio_mode`, `set_gpio_output` and `get_gpio_input` hide
+//! those encodings. Every output is open-drain: "high" releases the pin and
+//! needs an external pull-up.
+//!
+//! [`AxpGpios`] splits a driver in a `RefCell` into six [`AxpPin`]s that
+//! implement `embedded_hal::digital::{OutputPin, StatefulOutputPin, InputPin}`,
+//! so they can be handed to other driver crates. Each pin borrows the driver
+//! only for the duration of one register access, so the driver stays usable
+//! through the `RefCell` in between. [`AxpGpiosAsync`] and [`AxpPinAsync`] do
+//! the same for `Axp192Async` in an [`AxpMutex`](crate::AxpMutex), with
+//! `async` methods of the same names; a pin waits on the mutex while another
+//! pin or task is using the driver.
+
+use crate::AxpError;
+
+pub use crate::asynchronous::{AxpGpios as AxpGpiosAsync, AxpPin as AxpPinAsync};
+pub use crate::blocking::{AxpGpios, AxpPin};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GpioId {
+    Gpio0,
+    Gpio1,
+    Gpio2,
+    Gpio3,
+    Gpio4,
+    /// The N_RSTO pin, switched to GPIO mode.
+    Gpio5,
+}
+
+/// Digital function of a GPIO.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GpioMode {
+    /// NMOS open-drain output.
+    OpenDrainOutput,
+    /// Digital input.
+    Input,
+}
+
+impl<E: core::fmt::Debug> embedded_hal::digital::Error for AxpError<E> {
+    fn kind(&self) -> embedded_hal::digital::ErrorKind {
+        embedded_hal::digital::ErrorKind::Other
+    }
+}
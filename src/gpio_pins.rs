@@ -0,0 +1,164 @@
+use super::shared::{Shared, lock};
+use super::{Axp192, CurrentAxpDriverInterface, bisync, only_sync};
+use crate::AxpError;
+use crate::gpio::{GpioId, GpioMode};
+
+/// One AXP192 GPIO, sharing the driver with the other pins.
+pub struct AxpPin<'a, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    axp: &'a Shared<Axp192<I2CImpl, I2CBusErr>>,
+    id: GpioId,
+}
+
+/// The six GPIOs of a shared driver.
+pub struct AxpGpios<'a, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    pub gpio0: AxpPin<'a, I2CImpl, I2CBusErr>,
+    pub gpio1: AxpPin<'a, I2CImpl, I2CBusErr>,
+    pub gpio2: AxpPin<'a, I2CImpl, I2CBusErr>,
+    pub gpio3: AxpPin<'a, I2CImpl, I2CBusErr>,
+    pub gpio4: AxpPin<'a, I2CImpl, I2CBusErr>,
+    pub gpio5: AxpPin<'a, I2CImpl, I2CBusErr>,
+}
+
+impl<'a, I2CImpl, I2CBusErr> AxpGpios<'a, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    /// Splits `axp` into its pins. The pins keep their current mode; switch
+    /// them with [`AxpPin::set_mode`] before use.
+    pub fn new(axp: &'a Shared<Axp192<I2CImpl, I2CBusErr>>) -> Self {
+        let pin = |id| AxpPin { axp, id };
+        Self {
+            gpio0: pin(GpioId::Gpio0),
+            gpio1: pin(GpioId::Gpio1),
+            gpio2: pin(GpioId::Gpio2),
+            gpio3: pin(GpioId::Gpio3),
+            gpio4: pin(GpioId::Gpio4),
+            gpio5: pin(GpioId::Gpio5),
+        }
+    }
+}
+
+impl<I2CImpl, I2CBusErr> AxpPin<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    pub fn id(&self) -> GpioId {
+        self.id
+    }
+
+    #[bisync]
+    pub async fn set_mode(&mut self, mode: GpioMode) -> Result<(), AxpError<I2CBusErr>> {
+        lock(self.axp).await.set_gpio_mode(self.id, mode).await
+    }
+
+    /// Releases the pin (`true`) or pulls it low (`false`).
+    #[bisync]
+    pub async fn set_level(&mut self, high: bool) -> Result<(), AxpError<I2CBusErr>> {
+        lock(self.axp).await.set_gpio_output(self.id, high).await
+    }
+
+    #[bisync]
+    pub async fn set_high(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        self.set_level(true).await
+    }
+
+    #[bisync]
+    pub async fn set_low(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        self.set_level(false).await
+    }
+
+    /// Whether the output is released, as last written.
+    #[bisync]
+    pub async fn is_set_high(&mut self) -> Result<bool, AxpError<I2CBusErr>> {
+        lock(self.axp).await.get_gpio_output(self.id).await
+    }
+
+    #[bisync]
+    pub async fn is_set_low(&mut self) -> Result<bool, AxpError<I2CBusErr>> {
+        Ok(!self.is_set_high().await?)
+    }
+
+    #[bisync]
+    pub async fn toggle(&mut self) -> Result<(), AxpError<I2CBusErr>> {
+        let high = self.is_set_high().await?;
+        self.set_level(!high).await
+    }
+
+    /// The level sensed on the pin.
+    #[bisync]
+    pub async fn is_high(&mut self) -> Result<bool, AxpError<I2CBusErr>> {
+        lock(self.axp).await.get_gpio_input(self.id).await
+    }
+
+    #[bisync]
+    pub async fn is_low(&mut self) -> Result<bool, AxpError<I2CBusErr>> {
+        Ok(!self.is_high().await?)
+    }
+}
+
+impl<I2CImpl, I2CBusErr> embedded_hal::digital::ErrorType for AxpPin<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    type Error = AxpError<I2CBusErr>;
+}
+
+#[only_sync]
+impl<I2CImpl, I2CBusErr> embedded_hal::digital::OutputPin for AxpPin<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    fn set_low(&mut self) -> Result<(), Self::Error> {
+        AxpPin::set_low(self)
+    }
+
+    fn set_high(&mut self) -> Result<(), Self::Error> {
+        AxpPin::set_high(self)
+    }
+}
+
+#[only_sync]
+impl<I2CImpl, I2CBusErr> embedded_hal::digital::StatefulOutputPin for AxpPin<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    fn is_set_high(&mut self) -> Result<bool, Self::Error> {
+        AxpPin::is_set_high(self)
+    }
+
+    fn is_set_low(&mut self) -> Result<bool, Self::Error> {
+        AxpPin::is_set_low(self)
+    }
+
+    fn toggle(&mut self) -> Result<(), Self::Error> {
+        AxpPin::toggle(self)
+    }
+}
+
+#[only_sync]
+impl<I2CImpl, I2CBusErr> embedded_hal::digital::InputPin for AxpPin<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    fn is_high(&mut self) -> Result<bool, Self::Error> {
+        AxpPin::is_high(self)
+    }
+
+    fn is_low(&mut self) -> Result<bool, Self::Error> {
+        AxpPin::is_low(self)
+    }
+}
@@ -0,0 +1,306 @@
+//! Typed view of the AXP192 interrupt sources.
+//!
+//! The chip spreads its IRQ sources over five enable registers
+//! (`IrqEnableControl1`-`IrqEnableControl5`, REG40H-43H and REG4AH) and five
+//! matching status registers (`IrqStatus1`-`IrqStatus5`, REG44H-47H and REG4DH).
+//! Enable and status registers share the same bit layout, so a single
+//! [`AxpIrqSet`] describes both.
+
+/// Addresses of `IrqEnableControl1`-`IrqEnableControl5`, indexed by bank.
+pub(crate) const IRQ_ENABLE_ADDRESSES: [u8; 5] = [0x40, 0x41, 0x42, 0x43, 0x4A];
+/// Addresses of `IrqStatus1`-`IrqStatus5`, indexed by bank.
+pub(crate) const IRQ_STATUS_ADDRESSES: [u8; 5] = [0x44, 0x45, 0x46, 0x47, 0x4D];
+
+/// Bits that carry an IRQ source in each bank; the rest are reserved.
+const BANK_MASKS: [u8; 5] = [0xFE, 0xFF, 0xFB, 0xFD, 0x87];
+
+/// A single AXP192 interrupt source.
+///
+/// The discriminant encodes the register bank (upper bits) and the bit
+/// position inside that bank (lower three bits).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[repr(u8)]
+pub enum AxpIrq {
+    // Bank 0: IrqEnableControl1 / IrqStatus1
+    AcinOverVoltage = 7,
+    AcinInserted = 6,
+    AcinRemoved = 5,
+    VbusOverVoltage = 4,
+    VbusInserted = 3,
+    VbusRemoved = 2,
+    VbusBelowVhold = 1,
+    // Bank 1: IrqEnableControl2 / IrqStatus2
+    BatteryInserted = (1 << 3) | 7,
+    BatteryRemoved = (1 << 3) | 6,
+    BatteryActivationEntered = (1 << 3) | 5,
+    BatteryActivationExited = (1 << 3) | 4,
+    ChargeStarted = (1 << 3) | 3,
+    ChargeDone = (1 << 3) | 2,
+    BatteryOverTemp = (1 << 3) | 1,
+    BatteryUnderTemp = (1 << 3),
+    // Bank 2: IrqEnableControl3 / IrqStatus3
+    InternalOverTemp = (2 << 3) | 7,
+    ChargeCurrentInsufficient = (2 << 3) | 6,
+    Dcdc1VoltageLow = (2 << 3) | 5,
+    Dcdc2VoltageLow = (2 << 3) | 4,
+    Dcdc3VoltageLow = (2 << 3) | 3,
+    PekShortPress = (2 << 3) | 1,
+    PekLongPress = (2 << 3),
+    // Bank 3: IrqEnableControl4 / IrqStatus4
+    NoePowerOn = (3 << 3) | 7,
+    NoePowerOff = (3 << 3) | 6,
+    VbusValid = (3 << 3) | 5,
+    VbusInvalid = (3 << 3) | 4,
+    VbusSessionValid = (3 << 3) | 3,
+    VbusSessionEnd = (3 << 3) | 2,
+    ApsLowVoltageLevel2 = (3 << 3),
+    // Bank 4: IrqEnableControl5 / IrqStatus5
+    TimerTimeout = (4 << 3) | 7,
+    Gpio2Edge = (4 << 3) | 2,
+    Gpio1Edge = (4 << 3) | 1,
+    Gpio0Edge = (4 << 3),
+}
+
+impl AxpIrq {
+    /// Every interrupt source, in register order.
+    pub const ALL: [AxpIrq; 33] = [
+        AxpIrq::AcinOverVoltage,
+        AxpIrq::AcinInserted,
+        AxpIrq::AcinRemoved,
+        AxpIrq::VbusOverVoltage,
+        AxpIrq::VbusInserted,
+        AxpIrq::VbusRemoved,
+        AxpIrq::VbusBelowVhold,
+        AxpIrq::BatteryInserted,
+        AxpIrq::BatteryRemoved,
+        AxpIrq::BatteryActivationEntered,
+        AxpIrq::BatteryActivationExited,
+        AxpIrq::ChargeStarted,
+        AxpIrq::ChargeDone,
+        AxpIrq::BatteryOverTemp,
+        AxpIrq::BatteryUnderTemp,
+        AxpIrq::InternalOverTemp,
+        AxpIrq::ChargeCurrentInsufficient,
+        AxpIrq::Dcdc1VoltageLow,
+        AxpIrq::Dcdc2VoltageLow,
+        AxpIrq::Dcdc3VoltageLow,
+        AxpIrq::PekShortPress,
+        AxpIrq::PekLongPress,
+        AxpIrq::NoePowerOn,
+        AxpIrq::NoePowerOff,
+        AxpIrq::VbusValid,
+        AxpIrq::VbusInvalid,
+        AxpIrq::VbusSessionValid,
+        AxpIrq::VbusSessionEnd,
+        AxpIrq::ApsLowVoltageLevel2,
+        AxpIrq::TimerTimeout,
+        AxpIrq::Gpio2Edge,
+        AxpIrq::Gpio1Edge,
+        AxpIrq::Gpio0Edge,
+    ];
+
+    /// Index of the enable/status register pair holding this source (0-4).
+    pub const fn bank(self) -> usize {
+        (self as u8 >> 3) as usize
+    }
+
+    /// Bit mask of this source inside its bank.
+    pub const fn mask(self) -> u8 {
+        1 << (self as u8 & 0x07)
+    }
+
+    fn from_position(bank: usize, bit: u8) -> Option<Self> {
+        let position = ((bank as u8) << 3) | bit;
+        Self::ALL.iter().copied().find(|irq| *irq as u8 == position)
+    }
+}
+
+/// A set of [`AxpIrq`] sources, laid out exactly like the five IRQ registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct AxpIrqSet {
+    banks: [u8; 5],
+}
+
+impl AxpIrqSet {
+    /// The empty set.
+    pub const fn empty() -> Self {
+        Self { banks: [0; 5] }
+    }
+
+    /// Every interrupt source.
+    pub const fn all() -> Self {
+        Self { banks: BANK_MASKS }
+    }
+
+    /// Builds a set from raw register values, ignoring reserved bits.
+    pub const fn from_banks(banks: [u8; 5]) -> Self {
+        let mut masked = [0u8; 5];
+        let mut i = 0;
+        while i < 5 {
+            masked[i] = banks[i] & BANK_MASKS[i];
+            i += 1;
+        }
+        Self { banks: masked }
+    }
+
+    /// Raw register values for `IrqEnableControl1-5` / `IrqStatus1-5`.
+    pub const fn banks(&self) -> [u8; 5] {
+        self.banks
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        let mut i = 0;
+        while i < 5 {
+            if self.banks[i] != 0 {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+
+    pub const fn contains(&self, irq: AxpIrq) -> bool {
+        self.banks[irq.bank()] & irq.mask() != 0
+    }
+
+    pub const fn with(mut self, irq: AxpIrq) -> Self {
+        self.banks[irq.bank()] |= irq.mask();
+        self
+    }
+
+    pub fn insert(&mut self, irq: AxpIrq) {
+        self.banks[irq.bank()] |= irq.mask();
+    }
+
+    pub fn remove(&mut self, irq: AxpIrq) {
+        self.banks[irq.bank()] &= !irq.mask();
+    }
+
+    pub fn union(mut self, other: Self) -> Self {
+        self |= other;
+        self
+    }
+
+    pub fn intersection(mut self, other: Self) -> Self {
+        self &= other;
+        self
+    }
+
+    pub fn difference(mut self, other: Self) -> Self {
+        for (l, r) in self.banks.iter_mut().zip(other.banks) {
+            *l &= !r;
+        }
+        self
+    }
+
+    /// Removes and returns the first source in register order, if any.
+    pub fn pop(&mut self) -> Option<AxpIrq> {
+        let irq = self.iter().next()?;
+        self.remove(irq);
+        Some(irq)
+    }
+
+    pub fn iter(&self) -> AxpIrqIter {
+        AxpIrqIter {
+            set: *self,
+            position: 0,
+        }
+    }
+}
+
+impl From<AxpIrq> for AxpIrqSet {
+    fn from(irq: AxpIrq) -> Self {
+        Self::empty().with(irq)
+    }
+}
+
+impl FromIterator<AxpIrq> for AxpIrqSet {
+    fn from_iter<T: IntoIterator<Item = AxpIrq>>(iter: T) -> Self {
+        let mut set = Self::empty();
+        for irq in iter {
+            set.insert(irq);
+        }
+        set
+    }
+}
+
+impl IntoIterator for AxpIrqSet {
+    type Item = AxpIrq;
+    type IntoIter = AxpIrqIter;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl core::ops::BitOr for AxpIrqSet {
+    type Output = Self;
+    fn bitor(mut self, rhs: Self) -> Self::Output {
+        self |= rhs;
+        self
+    }
+}
+
+impl core::ops::BitOrAssign for AxpIrqSet {
+    fn bitor_assign(&mut self, rhs: Self) {
+        for (l, r) in self.banks.iter_mut().zip(rhs.banks) {
+            *l |= r;
+        }
+    }
+}
+
+impl core::ops::BitAnd for AxpIrqSet {
+    type Output = Self;
+    fn bitand(mut self, rhs: Self) -> Self::Output {
+        self &= rhs;
+        self
+    }
+}
+
+impl core::ops::BitAndAssign for AxpIrqSet {
+    fn bitand_assign(&mut self, rhs: Self) {
+        for (l, r) in self.banks.iter_mut().zip(rhs.banks) {
+            *l &= r;
+        }
+    }
+}
+
+impl core::ops::BitOr<AxpIrq> for AxpIrqSet {
+    type Output = Self;
+    fn bitor(self, rhs: AxpIrq) -> Self::Output {
+        self.with(rhs)
+    }
+}
+
+impl core::ops::BitOr for AxpIrq {
+    type Output = AxpIrqSet;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        AxpIrqSet::from(self).with(rhs)
+    }
+}
+
+/// Iterator over the sources in an [`AxpIrqSet`], in register order.
+#[derive(Debug, Clone)]
+pub struct AxpIrqIter {
+    set: AxpIrqSet,
+    position: u8,
+}
+
+impl Iterator for AxpIrqIter {
+    type Item = AxpIrq;
+    fn next(&mut self) -> Option<AxpIrq> {
+        while self.position < 40 {
+            let bank = (self.position / 8) as usize;
+            // Walk each bank from bit 7 down so iteration follows `AxpIrq::ALL`.
+            let bit = 7 - (self.position % 8);
+            self.position += 1;
+            if self.set.banks[bank] & (1 << bit) != 0
+                && let Some(irq) = AxpIrq::from_position(bank, bit)
+            {
+                return Some(irq);
+            }
+        }
+        None
+    }
+}
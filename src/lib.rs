@@ -22,24 +22,26 @@
 //!
 //! ```rust,no_run
 //! # use embedded_hal::i2c::I2c;
-//! # use axp192_dd::{Axp192, DcId};
-//! let i2c_bus = todo!();
+//! # use axp192_dd::{Axp192, AxpError, DcId};
+//! # fn example<I: I2c>(i2c_bus: I) -> Result<(), AxpError<I::Error>> {
 //! let mut axp = Axp192::new(i2c_bus);
 //!
 //! axp.set_dcdc_voltage(DcId::Dcdc1, 3300)?;
 //! # Ok(())
+//! # }
 //! ```
 //!
 //! For async environments, use `Axp192Async` (re-exported from the `asynchronous` module):
 //!
 //! ```rust,no_run
 //! # use embedded_hal_async::i2c::I2c;
-//! # use axp192_dd::{Axp192Async, DcId};
-//! let i2c_bus = todo!();
+//! # use axp192_dd::{Axp192Async, AxpError, DcId};
+//! # async fn example<I: I2c>(i2c_bus: I) -> Result<(), AxpError<I::Error>> {
 //! let mut axp = Axp192Async::new(i2c_bus);
 //!
 //! axp.set_dcdc_voltage(DcId::Dcdc1, 3300).await?;
 //! # Ok(())
+//! # }
 //! ```
 //!
 //! For a detailed register map, please refer to the `device.yaml` file in the
@@ -69,12 +71,55 @@
 #[macro_use]
 pub(crate) mod fmt;
 mod adc_helpers;
+pub mod adc_snapshot;
+pub mod backup;
+pub mod boards;
+pub mod charger;
+#[cfg(feature = "std")]
+pub mod cli;
+pub mod config;
+mod crc;
+pub mod events;
+pub mod fuel_gauge;
+pub mod gpio;
+pub mod irq;
+pub mod ntc;
+pub mod pek;
+pub mod power;
+pub mod pwm;
+pub mod register_dump;
+pub mod register_map;
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+pub mod snapshot;
+pub mod soc;
+pub mod units;
 
 use thiserror::Error;
 
 device_driver::create_device!(device_name: AxpLowLevel, manifest: "device.yaml");
 pub const AXP192_I2C_ADDRESS: u8 = 0x34;
 
+pub use adc_snapshot::AdcSnapshot;
+pub use backup::{BackupError, BackupRecord};
+pub use boards::{Board, Rail};
+pub use charger::{ChargerConfig, ChargerFault, ChargerState};
+pub use config::{PowerConfig, RailConfig};
+pub use events::{AxpEvent, AxpEventStream};
+pub use fuel_gauge::{CoulombCounts, SocEstimator};
+pub use gpio::{GpioId, GpioMode};
+pub use irq::{AxpIrq, AxpIrqSet};
+pub use ntc::TemperatureWindow;
+#[cfg(feature = "float")]
+pub use ntc::{Ntc, NtcModel};
+pub use pek::{PekConfig, PekPress};
+pub use power::WakeConfig;
+pub use pwm::{PwmId, PwmParameters};
+pub use register_dump::RegisterDump;
+pub use snapshot::{RegisterSnapshot, SnapshotError};
+pub use soc::{OcvCurve, OcvPoint, VoltageSocEstimator};
+pub use units::{DeciCelsius, MicroampHours, Microamps, Microwatts, Millivolts};
+
 #[derive(Debug, Error)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub enum AxpError<I2cErr> {
@@ -84,8 +129,20 @@ pub enum AxpError<I2cErr> {
     InvalidVoltage(u16),
     #[error("Invalid current: {0}mA for setting")]
     InvalidCurrent(u16),
+    #[error("Invalid frequency: {0}kHz for setting")]
+    InvalidFrequency(u16),
+    #[error("Invalid PWM frequency: {0}Hz")]
+    InvalidPwmFrequency(u32),
+    #[error("Invalid percentage: {0}% for setting")]
+    InvalidPercentage(u8),
+    #[error(
+        "Inverted temperature window: low-temperature threshold {0}mV is not above high-temperature threshold {1}mV"
+    )]
+    InvertedTemperatureWindow(u16, u16),
     #[error("Feature or specific mode not supported/implemented: {0}")]
     NotSupported(&'static str),
+    #[error("IRQ pin error: {0:?}")]
+    IrqPin(embedded_hal::digital::ErrorKind),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -104,6 +161,16 @@ pub enum LdoId {
     Ldo3,
 }
 
+/// GPIO pins that can be sampled by the ADC (`GpioVoltageAdc[0..4]`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum GpioAdcId {
+    Gpio0,
+    Gpio1,
+    Gpio2,
+    Gpio3,
+}
+
 pub struct AxpInterface<I2CBus> {
     i2c_bus: I2CBus,
 }
@@ -121,8 +188,15 @@ mod asynchronous {
     use embedded_hal_async::i2c::I2c;
     mod driver;
     pub use driver::*;
+    mod gpio_pins;
+    pub use gpio_pins::*;
+    mod pwm_channel;
+    pub use pwm_channel::*;
+    mod shared;
+    pub use shared::AxpMutex;
 }
 pub use asynchronous::Axp192 as Axp192Async;
+pub use asynchronous::AxpMutex;
 
 #[path = "."]
 mod blocking {
@@ -132,5 +206,13 @@ mod blocking {
     #[allow(clippy::duplicate_mod)]
     mod driver;
     pub use driver::*;
+    #[allow(clippy::duplicate_mod)]
+    mod gpio_pins;
+    pub use gpio_pins::*;
+    #[allow(clippy::duplicate_mod)]
+    mod pwm_channel;
+    pub use pwm_channel::*;
+    #[allow(clippy::duplicate_mod)]
+    mod shared;
 }
 pub use blocking::Axp192;
@@ -0,0 +1,206 @@
+//! Battery temperature sensing with an NTC thermistor on the TS pin.
+//!
+//! The AXP192 drives a constant bias current (`TsPinCurrentValue`) out of the
+//! TS pin into the battery NTC and compares the resulting voltage against the
+//! four `Battery*TempThreshold` registers (REG38H, REG39H, REG3CH, REG3DH).
+//! The NTC resistance falls as the battery warms, so a lower pin voltage means
+//! a higher temperature. [`TemperatureWindow`] holds the four thresholds in
+//! millivolts, and with the `float` feature [`Ntc`] models the thermistor and
+//! converts between degrees Celsius and TS pin millivolts.
+
+use crate::{AxpError, TsPinCurrentValue};
+
+/// Highest voltage a temperature threshold register can hold: 0xFF * 12.8mV.
+pub const TEMP_THRESHOLD_MAX_MV: u16 = 3264;
+
+impl TsPinCurrentValue {
+    /// The bias current in µA.
+    pub const fn ua(self) -> u16 {
+        20 + self as u16 * 20
+    }
+}
+
+/// Register value of a temperature threshold, rounded to the nearest 12.8mV step.
+pub(crate) const fn temp_threshold_raw_from_mv(threshold_mv: u16) -> Option<u8> {
+    if threshold_mv > TEMP_THRESHOLD_MAX_MV {
+        return None;
+    }
+    Some(((threshold_mv as u32 * 10 + 64) / 128) as u8)
+}
+
+/// Voltage of a temperature threshold register value.
+pub(crate) const fn temp_threshold_mv_from_raw(raw: u8) -> u16 {
+    ((raw as u32 * 128 + 5) / 10) as u16
+}
+
+/// Register values of a low and high temperature threshold pair. The low
+/// temperature threshold is the higher voltage and must stay above the other.
+pub(crate) fn temp_threshold_pair<E>(
+    low_temp_mv: u16,
+    high_temp_mv: u16,
+) -> Result<(u8, u8), AxpError<E>> {
+    let low_raw =
+        temp_threshold_raw_from_mv(low_temp_mv).ok_or(AxpError::InvalidVoltage(low_temp_mv))?;
+    let high_raw =
+        temp_threshold_raw_from_mv(high_temp_mv).ok_or(AxpError::InvalidVoltage(high_temp_mv))?;
+    if low_raw <= high_raw {
+        return Err(AxpError::InvertedTemperatureWindow(
+            low_temp_mv,
+            high_temp_mv,
+        ));
+    }
+    Ok((low_raw, high_raw))
+}
+
+/// TS pin voltage thresholds of the charge and discharge temperature windows.
+///
+/// Charging (or discharging) is allowed while the pin voltage lies between the
+/// high-temperature and the low-temperature threshold, so each `*_low_temp_mv`
+/// must be above its `*_high_temp_mv`. Values are 0-3264mV, rounded to 12.8mV steps.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TemperatureWindow {
+    /// `BatteryChargeLowTempThreshold` (REG38H).
+    pub charge_low_temp_mv: u16,
+    /// `BatteryChargeHighTempThreshold` (REG39H).
+    pub charge_high_temp_mv: u16,
+    /// `BatteryDischargeLowTempThreshold` (REG3CH).
+    pub discharge_low_temp_mv: u16,
+    /// `BatteryDischargeHighTempThreshold` (REG3DH).
+    pub discharge_high_temp_mv: u16,
+}
+
+impl TemperatureWindow {
+    /// The register reset values listed in `device.yaml`.
+    pub const DEFAULT: TemperatureWindow = TemperatureWindow {
+        charge_low_temp_mv: temp_threshold_mv_from_raw(0xA5),
+        charge_high_temp_mv: temp_threshold_mv_from_raw(0x1F),
+        discharge_low_temp_mv: temp_threshold_mv_from_raw(0xFC),
+        discharge_high_temp_mv: temp_threshold_mv_from_raw(0x16),
+    };
+
+    /// Checks both windows and converts them to register values, in the
+    /// order REG38H, REG39H, REG3CH, REG3DH.
+    pub(crate) fn encode<E>(&self) -> Result<[u8; 4], AxpError<E>> {
+        let (charge_low, charge_high) =
+            temp_threshold_pair(self.charge_low_temp_mv, self.charge_high_temp_mv)?;
+        let (discharge_low, discharge_high) =
+            temp_threshold_pair(self.discharge_low_temp_mv, self.discharge_high_temp_mv)?;
+        Ok([charge_low, charge_high, discharge_low, discharge_high])
+    }
+}
+
+impl Default for TemperatureWindow {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// 0°C in kelvin.
+#[cfg(feature = "float")]
+const ZERO_CELSIUS_K: f32 = 273.15;
+
+/// 25°C in kelvin, the reference point of the Beta model.
+#[cfg(feature = "float")]
+const T25_K: f32 = ZERO_CELSIUS_K + 25.0;
+
+/// Full scale of the 12-bit TS pin ADC: 4095 * 0.8mV.
+#[cfg(feature = "float")]
+const TS_ADC_FULL_SCALE_MV: f32 = 3276.0;
+
+/// Resistance-temperature relation of a thermistor.
+#[cfg(feature = "float")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum NtcModel {
+    /// `R = r25 * exp(beta * (1/T - 1/298.15K))`, from the datasheet R25 and B25/85 values.
+    Beta { r25_ohms: f32, beta: f32 },
+    /// `1/T = a + b*ln(R) + c*ln(R)^3`, with T in kelvin and R in ohms.
+    SteinhartHart { a: f32, b: f32, c: f32 },
+}
+
+/// An NTC thermistor on the TS pin and the bias current driving it.
+#[cfg(feature = "float")]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Ntc {
+    pub model: NtcModel,
+    pub bias_current: TsPinCurrentValue,
+}
+
+#[cfg(feature = "float")]
+impl Ntc {
+    /// A 10kΩ, B = 3950K thermistor at the 80µA reset bias current. The
+    /// reset thresholds of the AXP192 correspond to roughly 5-42°C for charging.
+    pub const NTC_10K_3950: Ntc = Ntc::beta(10_000.0, 3950.0, TsPinCurrentValue::Ua80);
+
+    pub const fn beta(r25_ohms: f32, beta: f32, bias_current: TsPinCurrentValue) -> Self {
+        Self {
+            model: NtcModel::Beta { r25_ohms, beta },
+            bias_current,
+        }
+    }
+
+    pub const fn steinhart_hart(a: f32, b: f32, c: f32, bias_current: TsPinCurrentValue) -> Self {
+        Self {
+            model: NtcModel::SteinhartHart { a, b, c },
+            bias_current,
+        }
+    }
+
+    /// Thermistor resistance at `temperature_c`.
+    pub fn resistance_ohms(&self, temperature_c: f32) -> f32 {
+        let t = temperature_c + ZERO_CELSIUS_K;
+        match self.model {
+            NtcModel::Beta { r25_ohms, beta } => {
+                r25_ohms * libm::expf(beta * (1.0 / t - 1.0 / T25_K))
+            }
+            NtcModel::SteinhartHart { a, b, c } => {
+                // Closed-form inverse of the cubic in ln(R).
+                let x = (a - 1.0 / t) / c;
+                let y = libm::sqrtf(libm::powf(b / (3.0 * c), 3.0) + x * x / 4.0);
+                libm::expf(libm::cbrtf(y - x / 2.0) - libm::cbrtf(y + x / 2.0))
+            }
+        }
+    }
+
+    /// Temperature at which the thermistor has `resistance_ohms`.
+    pub fn temperature_c(&self, resistance_ohms: f32) -> f32 {
+        let ln_r = libm::logf(resistance_ohms);
+        let t = match self.model {
+            NtcModel::Beta { r25_ohms, beta } => {
+                1.0 / (1.0 / T25_K + (ln_r - libm::logf(r25_ohms)) / beta)
+            }
+            NtcModel::SteinhartHart { a, b, c } => 1.0 / (a + b * ln_r + c * ln_r * ln_r * ln_r),
+        };
+        t - ZERO_CELSIUS_K
+    }
+
+    /// TS pin voltage with the thermistor at `temperature_c`.
+    pub fn pin_voltage_mv(&self, temperature_c: f32) -> f32 {
+        // Ω * µA = µV.
+        self.resistance_ohms(temperature_c) * self.bias_current.ua() as f32 / 1000.0
+    }
+
+    /// Temperature for a TS pin reading, or `None` when the reading is at
+    /// either end of the ADC range (shorted pin, or no thermistor fitted).
+    pub fn temperature_from_mv(&self, pin_mv: f32) -> Option<f32> {
+        if pin_mv <= 0.0 || pin_mv >= TS_ADC_FULL_SCALE_MV {
+            return None;
+        }
+        let resistance_ohms = pin_mv * 1000.0 / self.bias_current.ua() as f32;
+        Some(self.temperature_c(resistance_ohms))
+    }
+}
+
+/// Register values of the low and high temperature thresholds for a
+/// `min_c`-`max_c` window. The colder end gives the higher voltage.
+#[cfg(feature = "float")]
+pub(crate) fn ntc_threshold_pair<E>(
+    ntc: &Ntc,
+    min_c: f32,
+    max_c: f32,
+) -> Result<(u8, u8), AxpError<E>> {
+    let mv = |temperature_c: f32| libm::roundf(ntc.pin_voltage_mv(temperature_c)) as u16;
+    temp_threshold_pair(mv(min_c), mv(max_c))
+}
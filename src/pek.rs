@@ -0,0 +1,127 @@
+//! Power Enable Key (PEK) button settings.
+//!
+//! `PekKeyParameters` (REG36H) sets how long the button must be held to power
+//! on, to register a long press and to force a shutdown. Each timing enum
+//! converts to and from a [`Duration`]. Presses are latched in `IrqStatus3`
+//! (REG46H) as `PekShortPress` and `PekLongPress` and decoded as [`PekPress`].
+
+use core::time::Duration;
+
+use crate::{AxpIrq, PekLongPressTime, PekPowerOnTime, PekShutdownTime, PwrokSignalDelay};
+
+/// Defines `duration()` and `from_duration()` for a timing enum with `millis()`.
+macro_rules! duration_conversions {
+    ($ty:ident: $($variant:ident),+) => {
+        impl $ty {
+            const ALL: &'static [$ty] = &[$($ty::$variant),+];
+
+            pub const fn duration(self) -> Duration {
+                Duration::from_millis(self.millis() as u64)
+            }
+
+            /// The longest setting not exceeding `duration`, or the shortest one.
+            pub fn from_duration(duration: Duration) -> Self {
+                Self::ALL
+                    .iter()
+                    .rev()
+                    .find(|value| value.duration() <= duration)
+                    .copied()
+                    .unwrap_or(Self::ALL[0])
+            }
+        }
+    };
+}
+
+impl PekPowerOnTime {
+    pub const fn millis(self) -> u16 {
+        match self {
+            PekPowerOnTime::Ms128 => 128,
+            PekPowerOnTime::Ms512 => 512,
+            PekPowerOnTime::S1 => 1000,
+            PekPowerOnTime::S2 => 2000,
+        }
+    }
+}
+
+impl PekLongPressTime {
+    pub const fn millis(self) -> u16 {
+        1000 + self as u16 * 500
+    }
+}
+
+impl PekShutdownTime {
+    pub const fn millis(self) -> u16 {
+        4000 + self as u16 * 2000
+    }
+}
+
+impl PwrokSignalDelay {
+    pub const fn millis(self) -> u16 {
+        match self {
+            PwrokSignalDelay::Ms32 => 32,
+            PwrokSignalDelay::Ms64 => 64,
+        }
+    }
+}
+
+duration_conversions!(PekPowerOnTime: Ms128, Ms512, S1, S2);
+duration_conversions!(PekLongPressTime: S10, S15, S20, S25);
+duration_conversions!(PekShutdownTime: S4, S6, S8, S10);
+duration_conversions!(PwrokSignalDelay: Ms32, Ms64);
+
+/// Configuration of `PekKeyParameters`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PekConfig {
+    pub power_on_time: PekPowerOnTime,
+    pub long_press_time: PekLongPressTime,
+    pub shutdown_time: PekShutdownTime,
+    /// Power off when the key is held longer than `shutdown_time`.
+    pub auto_shutdown: bool,
+    pub pwrok_delay: PwrokSignalDelay,
+}
+
+impl PekConfig {
+    /// The register reset values listed in `device.yaml`.
+    pub const DEFAULT: PekConfig = PekConfig {
+        power_on_time: PekPowerOnTime::Ms512,
+        long_press_time: PekLongPressTime::S15,
+        shutdown_time: PekShutdownTime::S6,
+        auto_shutdown: true,
+        pwrok_delay: PwrokSignalDelay::Ms64,
+    };
+}
+
+impl Default for PekConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// A power key press latched by the AXP192.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PekPress {
+    /// Released before the long-press time.
+    Short,
+    /// Held for at least the long-press time.
+    Long,
+}
+
+impl PekPress {
+    /// The press reported by an IRQ, e.g. an event from `AxpEventStream`.
+    pub const fn from_irq(irq: AxpIrq) -> Option<Self> {
+        match irq {
+            AxpIrq::PekShortPress => Some(PekPress::Short),
+            AxpIrq::PekLongPress => Some(PekPress::Long),
+            _ => None,
+        }
+    }
+
+    pub const fn irq(self) -> AxpIrq {
+        match self {
+            PekPress::Short => AxpIrq::PekShortPress,
+            PekPress::Long => AxpIrq::PekLongPress,
+        }
+    }
+}
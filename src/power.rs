@@ -0,0 +1,72 @@
+//! Shutdown, sleep and wake-up.
+//!
+//! `Axp192::power_off` sets `request_shutdown_mode_a` in
+//! `ShutdownBatChgLedControl` (REG32H): every output turns off until the next
+//! power-on event, a PEK press or a power source being plugged in.
+//! `Axp192::power_off_with` first hands the six `DataBuffer` registers
+//! (REG06H-REG0BH) to a closure; see [`crate::backup`] for what survives there.
+//! The chip also shuts down on its own when APS drops below the VOFF voltage of
+//! `ShutdownVoltageSetting` (REG31H).
+//!
+//! `Axp192::sleep` turns off the rails a [`WakeConfig`] does not keep and arms
+//! the PEK short-press wake-up. Which rails come back on wake-up depends on the
+//! chip's power-on defaults, so re-apply the configuration afterwards, e.g. from
+//! a `RegisterSnapshot` saved before going to sleep.
+
+use crate::VoffVoltageValue;
+
+impl VoffVoltageValue {
+    /// The shutdown voltage in mV.
+    pub const fn mv(self) -> u16 {
+        2600 + self as u16 * 100
+    }
+
+    /// The highest setting not exceeding `voltage_mv`, or `None` outside 2600-3300mV.
+    pub fn from_mv(voltage_mv: u16) -> Option<Self> {
+        if !(2600..=3300).contains(&voltage_mv) {
+            return None;
+        }
+        Self::try_from(((voltage_mv - 2600) / 100) as u8).ok()
+    }
+}
+
+/// What stays powered during [`Axp192::sleep`](crate::Axp192::sleep) and what
+/// wakes the chip up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WakeConfig {
+    pub dcdc1: bool,
+    pub dcdc2: bool,
+    pub dcdc3: bool,
+    pub ldo2: bool,
+    pub ldo3: bool,
+    pub exten: bool,
+    /// Keep the GPIO0 LDO running; otherwise a GPIO0 in LDO mode is left floating.
+    pub gpio0_ldo: bool,
+    /// Switch every ADC off to save their supply current.
+    pub disable_adcs: bool,
+    /// Wake up on a short PEK press.
+    pub pek_wakeup: bool,
+}
+
+impl WakeConfig {
+    /// Keeps DC-DC1, which supplies the host on most boards, and wakes on a
+    /// short PEK press.
+    pub const DEFAULT: WakeConfig = WakeConfig {
+        dcdc1: true,
+        dcdc2: false,
+        dcdc3: false,
+        ldo2: false,
+        ldo3: false,
+        exten: false,
+        gpio0_ldo: false,
+        disable_adcs: true,
+        pek_wakeup: true,
+    };
+}
+
+impl Default for WakeConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
@@ -0,0 +1,96 @@
+//! PWM1 and PWM2 outputs on GPIO1 and GPIO2.
+//!
+//! Each generator has three parameters: X (`Pwm*FrequencySetting`), Y1 and Y2
+//! (`Pwm*DutyCycleSettingY1/Y2`). The output runs at 2.25MHz / (X + 1) / Y1
+//! with a duty cycle of Y2 / Y1. Y2 is only five bits wide, so
+//! [`solve_frequency`] keeps Y1 at 31 or below to leave the full duty range
+//! reachable; that sets the lowest frequency to about 284Hz.
+//!
+//! [`AxpPwm`] and [`AxpPwmAsync`] are channel objects sharing a driver in a
+//! `RefCell` or an [`AxpMutex`](crate::AxpMutex), like the GPIO pins; the
+//! blocking one implements
+//! `embedded_hal::pwm::SetDutyCycle`.
+
+use crate::AxpError;
+
+pub use crate::asynchronous::AxpPwm as AxpPwmAsync;
+pub use crate::blocking::AxpPwm;
+
+/// Input clock of the PWM generators.
+pub const PWM_CLOCK_HZ: u32 = 2_250_000;
+/// Largest Y2, and so the largest usable Y1.
+pub const PWM_Y2_MAX: u8 = 31;
+/// Lowest frequency [`solve_frequency`] accepts: X = 255, Y1 = 31.
+pub const PWM_MIN_FREQUENCY_HZ: u32 = PWM_CLOCK_HZ / 256 / PWM_Y2_MAX as u32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PwmId {
+    /// Output on GPIO1.
+    Pwm1,
+    /// Output on GPIO2.
+    Pwm2,
+}
+
+impl PwmId {
+    /// Address of `Pwm*FrequencySetting`; Y1 and Y2 follow it.
+    pub(crate) const fn base_address(self) -> u8 {
+        match self {
+            PwmId::Pwm1 => 0x98,
+            PwmId::Pwm2 => 0x9B,
+        }
+    }
+}
+
+/// Raw parameters of a PWM generator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PwmParameters {
+    pub x: u8,
+    /// Period in counts, 1-255. Zero stops the output.
+    pub y1: u8,
+    /// High time in counts, 0-31.
+    pub y2: u8,
+}
+
+impl PwmParameters {
+    /// Output frequency, rounded to the nearest Hz. Zero if Y1 is zero.
+    pub const fn frequency_hz(&self) -> u32 {
+        let divider = (self.x as u32 + 1) * self.y1 as u32;
+        if divider == 0 {
+            return 0;
+        }
+        (PWM_CLOCK_HZ + divider / 2) / divider
+    }
+}
+
+/// The X and Y1 giving the frequency closest to `frequency_hz`, preferring the
+/// largest Y1 for duty cycle resolution. `None` outside
+/// [`PWM_MIN_FREQUENCY_HZ`]-[`PWM_CLOCK_HZ`].
+pub fn solve_frequency(frequency_hz: u32) -> Option<(u8, u8)> {
+    if !(PWM_MIN_FREQUENCY_HZ..=PWM_CLOCK_HZ).contains(&frequency_hz) {
+        return None;
+    }
+    // Best (x, y1) with its error |clock / divider - f| as a fraction.
+    let mut best: Option<(u8, u8, u64, u64)> = None;
+    for y1 in (1..=PWM_Y2_MAX as u32).rev() {
+        let per_count = frequency_hz * y1;
+        let x_plus_1 = ((PWM_CLOCK_HZ + per_count / 2) / per_count).clamp(1, 256);
+        let divider = (x_plus_1 * y1) as u64;
+        let error = (PWM_CLOCK_HZ as u64).abs_diff(frequency_hz as u64 * divider);
+        let better = match best {
+            Some((_, _, best_error, best_divider)) => error * best_divider < best_error * divider,
+            None => true,
+        };
+        if better {
+            best = Some(((x_plus_1 - 1) as u8, y1 as u8, error, divider));
+        }
+    }
+    best.map(|(x, y1, _, _)| (x, y1))
+}
+
+impl<E: core::fmt::Debug> embedded_hal::pwm::Error for AxpError<E> {
+    fn kind(&self) -> embedded_hal::pwm::ErrorKind {
+        embedded_hal::pwm::ErrorKind::Other
+    }
+}
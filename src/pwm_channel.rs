@@ -0,0 +1,106 @@
+use super::shared::{Shared, lock};
+use super::{Axp192, CurrentAxpDriverInterface, bisync, only_sync};
+use crate::pwm::{PWM_MIN_FREQUENCY_HZ, PWM_Y2_MAX};
+use crate::{AxpError, PwmId};
+
+/// A PWM generator driving GPIO1 or GPIO2, sharing the driver with other
+/// pins and channels.
+///
+/// The duty cycle is set in Y1 counts, so [`max_duty_cycle`](Self::max_duty_cycle)
+/// changes with the frequency.
+pub struct AxpPwm<'a, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    axp: &'a Shared<Axp192<I2CImpl, I2CBusErr>>,
+    id: PwmId,
+    /// Y1, at most [`PWM_Y2_MAX`].
+    max_duty: u8,
+}
+
+impl<'a, I2CImpl, I2CBusErr> AxpPwm<'a, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    /// Switches the GPIO of `id` to PWM output and takes over the generator at
+    /// its current frequency and duty cycle.
+    ///
+    /// A Y1 above [`PWM_Y2_MAX`] leaves part of the duty range unreachable, so
+    /// the current frequency is then re-solved with a smaller Y1, raised to
+    /// [`PWM_MIN_FREQUENCY_HZ`] if it is below.
+    #[bisync]
+    pub async fn new(
+        axp: &'a Shared<Axp192<I2CImpl, I2CBusErr>>,
+        id: PwmId,
+    ) -> Result<Self, AxpError<I2CBusErr>> {
+        let mut driver = lock(axp).await;
+        driver.enable_pwm_output(id).await?;
+        let mut parameters = driver.get_pwm_parameters(id).await?;
+        if parameters.y1 > PWM_Y2_MAX {
+            let frequency_hz = parameters.frequency_hz().max(PWM_MIN_FREQUENCY_HZ);
+            driver.set_pwm_frequency_hz(id, frequency_hz).await?;
+            parameters = driver.get_pwm_parameters(id).await?;
+        }
+        drop(driver);
+        Ok(Self {
+            axp,
+            id,
+            max_duty: parameters.y1,
+        })
+    }
+
+    pub fn id(&self) -> PwmId {
+        self.id
+    }
+
+    /// Sets the frequency closest to `frequency_hz`, keeping the duty cycle,
+    /// and returns the frequency set.
+    #[bisync]
+    pub async fn set_frequency_hz(
+        &mut self,
+        frequency_hz: u32,
+    ) -> Result<u32, AxpError<I2CBusErr>> {
+        let mut driver = lock(self.axp).await;
+        let frequency_hz = driver.set_pwm_frequency_hz(self.id, frequency_hz).await?;
+        self.max_duty = driver.get_pwm_parameters(self.id).await?.y1;
+        Ok(frequency_hz)
+    }
+
+    /// The duty cycle value for 100%: Y1.
+    pub fn max_duty_cycle(&self) -> u16 {
+        self.max_duty as u16
+    }
+
+    /// Sets the high time to `duty` out of [`max_duty_cycle`](Self::max_duty_cycle)
+    /// counts. Larger values are clamped to 100%.
+    #[bisync]
+    pub async fn set_duty_cycle(&mut self, duty: u16) -> Result<(), AxpError<I2CBusErr>> {
+        let duty = duty.min(self.max_duty as u16) as u8;
+        lock(self.axp).await.set_pwm_duty(self.id, duty).await
+    }
+}
+
+impl<I2CImpl, I2CBusErr> embedded_hal::pwm::ErrorType for AxpPwm<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    type Error = AxpError<I2CBusErr>;
+}
+
+#[only_sync]
+impl<I2CImpl, I2CBusErr> embedded_hal::pwm::SetDutyCycle for AxpPwm<'_, I2CImpl, I2CBusErr>
+where
+    I2CImpl: CurrentAxpDriverInterface<I2CBusErr>,
+    I2CBusErr: core::fmt::Debug,
+{
+    fn max_duty_cycle(&self) -> u16 {
+        AxpPwm::max_duty_cycle(self)
+    }
+
+    fn set_duty_cycle(&mut self, duty: u16) -> Result<(), Self::Error> {
+        AxpPwm::set_duty_cycle(self, duty)
+    }
+}
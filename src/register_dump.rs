@@ -0,0 +1,105 @@
+//! Raw dump of every register and its field-by-field decode.
+//!
+//! [`RegisterDump`] holds the raw value of each entry of
+//! [`REGISTERS`](crate::register_map::REGISTERS), in the same order. Its
+//! `Display` and `defmt::Format` implementations print one line per field of
+//! [`register_map::fields`], with the field's raw value:
+//!
+//! ```text
+//! ChargeControl1.charge_enable = 1
+//! ChargeControl1.target_voltage = 2
+//! ChargeControl1.end_current_threshold = 0
+//! ChargeControl1.charge_current = 8
+//! ```
+//!
+//! [`RegisterDump::iter`] and [`decode`] give the generated field sets instead,
+//! whose `Debug` output shows enum fields by their variant names. Registers
+//! sharing a layout (`DataBuffer1`-`DataBuffer5`, `DcDc3VoltageSetting`, the
+//! PWM2 registers, `GpioVoltageAdc1`-`GpioVoltageAdc3`) decode through the
+//! field set of their first instance.
+
+use core::fmt;
+
+use device_driver::FieldSet;
+
+use crate::field_sets::FieldSetValue;
+use crate::register_map::{self, REGISTER_COUNT, REGISTERS, RegisterInfo};
+
+/// Raw `(address, value)` of every register, in [`REGISTERS`] order.
+///
+/// Multi-byte registers are big-endian, as transferred on the bus.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterDump(pub [(u8, u32); REGISTER_COUNT]);
+
+impl RegisterDump {
+    /// Raw value of the register starting at `address`.
+    pub fn get(&self, address: u8) -> Option<u32> {
+        self.0
+            .iter()
+            .find(|(entry, _)| *entry == address)
+            .map(|&(_, value)| value)
+    }
+
+    /// Every register with its metadata, raw value and decoded fields.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static RegisterInfo, u32, FieldSetValue)> + '_ {
+        REGISTERS
+            .iter()
+            .zip(self.0.iter())
+            .filter_map(|(info, &(address, value))| {
+                decode(address, value).map(|fields| (info, value, fields))
+            })
+    }
+}
+
+impl fmt::Display for RegisterDump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (info, &(_, value)) in REGISTERS.iter().zip(self.0.iter()) {
+            for field in register_map::fields(info.address) {
+                writeln!(f, "{}.{} = {}", info.name, field.name, field.extract(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterDump {
+    fn format(&self, f: defmt::Formatter) {
+        for (info, &(_, value)) in REGISTERS.iter().zip(self.0.iter()) {
+            for field in register_map::fields(info.address) {
+                defmt::write!(
+                    f,
+                    "{=str}.{=str} = {=u32}\n",
+                    info.name,
+                    field.name,
+                    field.extract(value)
+                );
+            }
+        }
+    }
+}
+
+/// A field set holding `bytes`, which must be `F::SIZE_BITS / 8` long.
+fn field_set<F: FieldSet>(bytes: &[u8]) -> F {
+    let mut fields = F::new_with_zero();
+    fields.get_inner_buffer_mut().copy_from_slice(bytes);
+    fields
+}
+
+macro_rules! decode_table {
+    ($($address:literal => $field_set:ident,)*) => {
+        /// Decodes `value`, the raw contents of the register starting at
+        /// `address`, into its field set. `None` if no register starts there.
+        pub fn decode(address: u8, value: u32) -> Option<FieldSetValue> {
+            let info = register_map::by_address(address).filter(|info| info.address == address)?;
+            let bytes = value.to_be_bytes();
+            let bytes = &bytes[4 - info.size_bytes as usize..];
+            match address {
+                $($address => Some(FieldSetValue::$field_set(field_set(bytes))),)*
+                _ => None,
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/register_decode.rs"));
@@ -0,0 +1,184 @@
+//! Register metadata of the AXP192, generated from `device.yaml`.
+//!
+//! The generated low-level API knows every register by type, but tools that
+//! work on all registers at once (the simulator, register dumps, command-line
+//! inspection) need the same information as data. [`REGISTERS`] lists every
+//! register of `device.yaml` sorted by address, with repeated blocks such as
+//! `GpioVoltageAdc` expanded into one entry per instance. [`fields`] gives the
+//! named bit fields of each register. The build script writes both tables from
+//! the manifest, so they follow every change to it.
+
+/// How the bus can access a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RegisterAccess {
+    ReadOnly,
+    ReadWrite,
+    /// Status flags cleared by writing 1 to them (`IrqStatus1`-`IrqStatus5`).
+    WriteOneToClear,
+}
+
+/// Description of one register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterInfo {
+    pub name: &'static str,
+    pub address: u8,
+    pub size_bytes: u8,
+    pub access: RegisterAccess,
+    /// Power-on value, `None` for measurement and status registers.
+    pub reset_value: Option<u32>,
+    /// Read-only status bits inside an otherwise writable register.
+    pub read_only_mask: u8,
+    /// Status bits inside an otherwise writable register that are cleared by
+    /// writing 1 to them, like `TimerControl.timeout_flag`.
+    pub write_one_to_clear_mask: u8,
+}
+
+impl RegisterInfo {
+    const fn new(
+        name: &'static str,
+        address: u8,
+        size_bytes: u8,
+        access: RegisterAccess,
+        reset_value: Option<u32>,
+        read_only_mask: u8,
+    ) -> Self {
+        Self {
+            name,
+            address,
+            size_bytes,
+            access,
+            reset_value,
+            read_only_mask,
+            write_one_to_clear_mask: 0x00,
+        }
+    }
+
+    const fn with_write_one_to_clear_mask(mut self, mask: u8) -> Self {
+        self.write_one_to_clear_mask = mask;
+        self
+    }
+
+    /// Whether `address` is one of the bytes of this register.
+    pub const fn contains(&self, address: u8) -> bool {
+        address >= self.address && address - self.address < self.size_bytes
+    }
+
+    pub const fn is_writable(&self) -> bool {
+        !matches!(self.access, RegisterAccess::ReadOnly)
+    }
+}
+
+/// Every register defined in `device.yaml`, sorted by address.
+pub const REGISTERS: [RegisterInfo; REGISTER_COUNT] =
+    include!(concat!(env!("OUT_DIR"), "/registers.rs"));
+
+/// Number of entries in [`REGISTERS`].
+pub const REGISTER_COUNT: usize = include!(concat!(env!("OUT_DIR"), "/register_count.rs"));
+
+/// Looks up a register by its `device.yaml` name, ignoring ASCII case.
+pub fn by_name(name: &str) -> Option<&'static RegisterInfo> {
+    REGISTERS
+        .iter()
+        .find(|register| register.name.eq_ignore_ascii_case(name))
+}
+
+/// Looks up the register that `address` belongs to, including the inner bytes
+/// of multi-byte registers.
+pub fn by_address(address: u8) -> Option<&'static RegisterInfo> {
+    REGISTERS.iter().find(|register| register.contains(address))
+}
+
+/// A bit field of a register, as named in `device.yaml`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldInfo {
+    pub name: &'static str,
+    /// Lowest bit of the field.
+    pub start: u8,
+    /// One past the highest bit of the field.
+    pub end: u8,
+}
+
+impl FieldInfo {
+    /// Bits of the register value covered by the field.
+    pub const fn mask(&self) -> u32 {
+        (((1u64 << (self.end - self.start)) - 1) << self.start) as u32
+    }
+
+    /// The field's value inside the register value `value`.
+    pub const fn extract(&self, value: u32) -> u32 {
+        (value & self.mask()) >> self.start
+    }
+}
+
+// A struct literal rather than a `const fn` call, so the field arrays below
+// are promoted to `'static`.
+macro_rules! field {
+    ($name:literal, $start:literal, $end:literal) => {
+        FieldInfo {
+            name: $name,
+            start: $start,
+            end: $end,
+        }
+    };
+}
+
+/// Fields of the register starting at `address`, most significant first.
+/// Empty if no register starts at `address`.
+pub fn fields(address: u8) -> &'static [FieldInfo] {
+    include!(concat!(env!("OUT_DIR"), "/register_fields.rs"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_are_sorted_and_disjoint() {
+        for pair in REGISTERS.windows(2) {
+            ::core::assert!(
+                pair[0].address + pair[0].size_bytes <= pair[1].address,
+                "{} overlaps {}",
+                pair[0].name,
+                pair[1].name
+            );
+        }
+    }
+
+    #[test]
+    fn refs_and_repeats_are_expanded() {
+        let dcdc3 = by_name("DcDc3VoltageSetting").unwrap();
+        ::core::assert_eq!(dcdc3.address, 0x27);
+        ::core::assert_eq!(dcdc3.reset_value, Some(0x48));
+        ::core::assert_eq!(fields(0x27), fields(0x26));
+
+        let gpio3 = by_name("GpioVoltageAdc3").unwrap_or_else(|| ::std::panic!("not expanded"));
+        ::core::assert_eq!(gpio3.address, 0x6A);
+        ::core::assert_eq!(gpio3.size_bytes, 2);
+        ::core::assert_eq!(by_address(0x6B), Some(gpio3));
+    }
+
+    #[test]
+    fn access_rules_come_from_the_manifest() {
+        ::core::assert_eq!(
+            by_name("PowerStatus").unwrap().access,
+            RegisterAccess::ReadOnly
+        );
+        ::core::assert_eq!(
+            by_name("IrqStatus5").unwrap().access,
+            RegisterAccess::WriteOneToClear
+        );
+        let timer = by_name("TimerControl").unwrap();
+        ::core::assert_eq!(timer.access, RegisterAccess::ReadWrite);
+        ::core::assert_eq!(timer.write_one_to_clear_mask, 0x80);
+        // GPIO0-2 input levels are read-only fields of a read-write register.
+        ::core::assert_eq!(
+            by_name("Gpio0To2SignalStatusAndControl")
+                .unwrap()
+                .read_only_mask,
+            0x70
+        );
+    }
+}
@@ -0,0 +1,35 @@
+//! Access to a driver shared by pin and channel objects: a `RefCell` for the
+//! blocking driver, an async mutex for the async one.
+
+#[only_sync]
+use core::cell::{RefCell, RefMut};
+
+#[only_async]
+use embassy_sync::blocking_mutex::raw::NoopRawMutex;
+#[only_async]
+use embassy_sync::mutex::{Mutex, MutexGuard};
+
+use super::{only_async, only_sync};
+
+#[only_sync]
+pub(super) type Shared<T> = RefCell<T>;
+
+/// The mutex `AxpGpiosAsync` and `AxpPwmAsync` share an `Axp192Async`
+/// through, for tasks running on one executor.
+#[only_async]
+pub type AxpMutex<T> = Mutex<NoopRawMutex, T>;
+
+#[only_async]
+pub(super) type Shared<T> = AxpMutex<T>;
+
+#[only_sync]
+pub(super) fn lock<T>(cell: &RefCell<T>) -> RefMut<'_, T> {
+    cell.borrow_mut()
+}
+
+/// Locks the driver, suspending the task while another pin, channel or task
+/// holds it.
+#[only_async]
+pub(super) async fn lock<T>(mutex: &AxpMutex<T>) -> MutexGuard<'_, NoopRawMutex, T> {
+    mutex.lock().await
+}
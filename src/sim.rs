@@ -0,0 +1,1089 @@
+//! In-memory AXP192 for host-side testing.
+//!
+//! [`SimAxp192`] implements both `embedded_hal::i2c::I2c` and
+//! `embedded_hal_async::i2c::I2c`, so it can be handed to [`Axp192`](crate::Axp192)
+//! or [`Axp192Async`](crate::Axp192Async) in place of a real bus. It behaves like
+//! the chip's register file as described by `device.yaml`:
+//!
+//! * registers start at their `reset_value` (measurement registers at 0),
+//! * writes to read-only registers and read-only status bits are ignored,
+//! * `IrqStatus1`-`IrqStatus5` and `TimerControl.timeout_flag` are write-1-to-clear,
+//! * `CoulombCounterControl` clear and pause requests self-clear, a clear
+//!   request zeroes both coulomb counters and a pause holds them until the
+//!   next write enabling them,
+//! * the EXTEN and DC-DC2 enables are shared between REG10H and REG12H,
+//! * multi-byte transfers auto-increment the register address.
+//!
+//! Test code sets measurements and status registers through
+//! [`SimAxp192::set_register`], which bypasses the access rules.
+//!
+//! On top of the register file, a behavioral model lets tests drive the
+//! chip's inputs: battery voltage, ACIN/VBUS presence, system load, die
+//! temperature, TS pin voltage and PEK presses. Every input change recomputes `PowerStatus`,
+//! `ChargeStatus`, `OtgVbusStatus` and the enabled ADC channels, and raises the
+//! matching `IrqStatus*` flags. [`SimAxp192::advance_ms`] lets time pass for
+//! the coulomb counters. Registers the model derives are only rewritten by these
+//! calls, so a test using [`SimAxp192::set_register`] alone keeps full control.
+//!
+//! ```
+//! use axp192_dd::sim::{SimAxp192, SimSupply};
+//! use axp192_dd::AxpIrq;
+//!
+//! let mut sim = SimAxp192::new();
+//! sim.set_battery(Some(4100));
+//! sim.set_vbus(Some(SimSupply::new(5000, 500)));
+//! sim.set_battery(Some(4200));
+//! assert!(sim.irq_pending(AxpIrq::ChargeDone));
+//! ```
+//!
+//! ```
+//! use axp192_dd::sim::SimAxp192;
+//! use axp192_dd::{Axp192, LdoId};
+//!
+//! let mut axp = Axp192::new(SimAxp192::new());
+//! axp.set_ldo_voltage_mv(LdoId::Ldo2, 3300).unwrap();
+//! ```
+
+use embedded_hal::i2c::{ErrorKind, NoAcknowledgeSource, Operation};
+
+use crate::ntc::temp_threshold_mv_from_raw;
+use crate::register_map::{self, RegisterAccess};
+use crate::{AXP192_I2C_ADDRESS, AxpIrq, DeciCelsius, field_sets};
+
+const COULOMB_COUNTERS_START: usize = 0xB0;
+const COULOMB_COUNTERS_LEN: usize = 8;
+const COULOMB_COUNTER_CONTROL: u8 = 0xB8;
+const COULOMB_CLEAR_REQUEST: u8 = 1 << 5;
+const COULOMB_PAUSE_REQUEST: u8 = 1 << 6;
+const COULOMB_ENABLE: u8 = 1 << 7;
+const EXTEN_DCDC2_CONTROL: u8 = 0x10;
+const POWER_OUTPUT_CONTROL: u8 = 0x12;
+const SHUTDOWN_CONTROL: u8 = 0x32;
+const SHUTDOWN_REQUEST: u8 = 1 << 7;
+
+/// Lowest ACIN/VBUS voltage the model treats as a usable supply.
+pub const SUPPLY_USABLE_MV: u16 = 4000;
+
+/// Voltage and available current of an external input (ACIN or VBUS).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SimSupply {
+    pub voltage_mv: u16,
+    /// Current drawn from the input, as reported by its current ADC.
+    pub current_ma: u16,
+}
+
+impl SimSupply {
+    pub const fn new(voltage_mv: u16, current_ma: u16) -> Self {
+        Self {
+            voltage_mv,
+            current_ma,
+        }
+    }
+
+    const fn usable(&self) -> bool {
+        self.voltage_mv >= SUPPLY_USABLE_MV
+    }
+}
+
+/// Inputs and derived state of the behavioral model.
+#[derive(Debug, Clone)]
+struct Model {
+    battery_mv: Option<u16>,
+    acin: Option<SimSupply>,
+    vbus: Option<SimSupply>,
+    system_load_ma: u16,
+    die_temperature: DeciCelsius,
+    ts_pin_mv: u16,
+    /// Whether the TS pin was below the high or above the low charge
+    /// temperature threshold at the last refresh.
+    battery_too_hot: bool,
+    battery_too_cold: bool,
+    charging: bool,
+    charge_done: bool,
+    aps_low: bool,
+    powered_off: bool,
+    /// Set by a pause request, cleared by the next write enabling the counters.
+    coulomb_paused: bool,
+    /// Sub-count remainders of the charge and discharge coulomb counters.
+    coulomb_remainders: [u64; 2],
+}
+
+impl Default for Model {
+    fn default() -> Self {
+        Self {
+            battery_mv: None,
+            acin: None,
+            vbus: None,
+            system_load_ma: 0,
+            die_temperature: DeciCelsius(250),
+            // A 10kΩ NTC at 25°C biased with the reset 80µA.
+            ts_pin_mv: 800,
+            battery_too_hot: false,
+            battery_too_cold: false,
+            charging: false,
+            charge_done: false,
+            aps_low: false,
+            powered_off: false,
+            coulomb_paused: false,
+            coulomb_remainders: [0; 2],
+        }
+    }
+}
+
+/// Simulated AXP192 register file with a behavioral power model.
+#[derive(Debug, Clone)]
+pub struct SimAxp192 {
+    registers: [u8; 256],
+    pointer: u8,
+    model: Model,
+}
+
+impl SimAxp192 {
+    /// A chip in its power-on state.
+    pub fn new() -> Self {
+        let mut registers = [0; 256];
+        for register in register_map::REGISTERS.iter() {
+            if let Some(reset_value) = register.reset_value {
+                let size = register.size_bytes as usize;
+                let bytes = reset_value.to_be_bytes();
+                let start = register.address as usize;
+                registers[start..start + size].copy_from_slice(&bytes[4 - size..]);
+            }
+        }
+        Self {
+            registers,
+            pointer: 0,
+            model: Model::default(),
+        }
+    }
+
+    /// Current value of the register byte at `address`.
+    pub fn register(&self, address: u8) -> u8 {
+        self.registers[address as usize]
+    }
+
+    /// Sets the register byte at `address`, ignoring access rules.
+    pub fn set_register(&mut self, address: u8, value: u8) {
+        self.registers[address as usize] = value;
+    }
+
+    /// Sets consecutive register bytes starting at `address`, ignoring access
+    /// rules, e.g. a big-endian ADC result.
+    pub fn set_registers(&mut self, address: u8, values: &[u8]) {
+        let start = address as usize;
+        self.registers[start..start + values.len()].copy_from_slice(values);
+    }
+
+    /// Raises `irq` as the chip would: its status flag is set only if the
+    /// source is enabled in `IrqEnableControl*`.
+    pub fn raise_irq(&mut self, irq: AxpIrq) {
+        let bank = irq.bank();
+        let enabled = self.registers[crate::irq::IRQ_ENABLE_ADDRESSES[bank] as usize];
+        if enabled & irq.mask() != 0 {
+            self.registers[crate::irq::IRQ_STATUS_ADDRESSES[bank] as usize] |= irq.mask();
+        }
+    }
+
+    /// Whether the status flag of `irq` is set.
+    pub fn irq_pending(&self, irq: AxpIrq) -> bool {
+        self.registers[crate::irq::IRQ_STATUS_ADDRESSES[irq.bank()] as usize] & irq.mask() != 0
+    }
+
+    /// Level of the open-drain IRQ output: `true` (pulled low) while any
+    /// enabled status flag is set.
+    pub fn irq_asserted(&self) -> bool {
+        crate::irq::IRQ_STATUS_ADDRESSES
+            .iter()
+            .zip(crate::irq::IRQ_ENABLE_ADDRESSES.iter())
+            .any(|(&status, &enable)| {
+                self.registers[status as usize] & self.registers[enable as usize] != 0
+            })
+    }
+
+    /// Connects a battery at `voltage_mv`, or removes it with `None`.
+    pub fn set_battery(&mut self, voltage_mv: Option<u16>) {
+        let was_present = self.model.battery_mv.is_some();
+        self.model.battery_mv = voltage_mv;
+        match (was_present, voltage_mv.is_some()) {
+            (false, true) => self.raise_irq(AxpIrq::BatteryInserted),
+            (true, false) => self.raise_irq(AxpIrq::BatteryRemoved),
+            _ => {}
+        }
+        self.refresh();
+    }
+
+    /// Plugs in the ACIN supply, or unplugs it with `None`.
+    pub fn set_acin(&mut self, supply: Option<SimSupply>) {
+        let was_present = self.model.acin.is_some();
+        self.model.acin = supply;
+        match (was_present, supply.is_some()) {
+            (false, true) => self.raise_irq(AxpIrq::AcinInserted),
+            (true, false) => self.raise_irq(AxpIrq::AcinRemoved),
+            _ => {}
+        }
+        self.refresh();
+    }
+
+    /// Plugs in the VBUS supply, or unplugs it with `None`.
+    pub fn set_vbus(&mut self, supply: Option<SimSupply>) {
+        let was_present = self.model.vbus.is_some();
+        let was_usable = self.model.vbus.is_some_and(|vbus| vbus.usable());
+        self.model.vbus = supply;
+        match (was_present, supply.is_some()) {
+            (false, true) => self.raise_irq(AxpIrq::VbusInserted),
+            (true, false) => self.raise_irq(AxpIrq::VbusRemoved),
+            _ => {}
+        }
+        match (was_usable, supply.is_some_and(|vbus| vbus.usable())) {
+            (false, true) => self.raise_irq(AxpIrq::VbusValid),
+            (true, false) => self.raise_irq(AxpIrq::VbusInvalid),
+            _ => {}
+        }
+        self.refresh();
+    }
+
+    /// Current the system draws from the battery while no external supply is usable.
+    pub fn set_system_load_ma(&mut self, current_ma: u16) {
+        self.model.system_load_ma = current_ma;
+        self.refresh();
+    }
+
+    pub fn set_die_temperature(&mut self, temperature: DeciCelsius) {
+        self.model.die_temperature = temperature;
+        self.refresh();
+    }
+
+    /// Sets the TS pin voltage. While the TS pin monitors the battery
+    /// temperature, charging stops outside the charge temperature window and
+    /// `BatteryOverTemp`/`BatteryUnderTemp` are raised on leaving it.
+    pub fn set_ts_pin_mv(&mut self, voltage_mv: u16) {
+        self.model.ts_pin_mv = voltage_mv;
+        self.refresh();
+    }
+
+    /// Presses and releases the power key after `duration_ms`.
+    ///
+    /// Raises `PekShortPress` or, from the configured long-press time on,
+    /// `PekLongPress`. Holding past the shutdown time with auto-shutdown
+    /// enabled powers the chip off.
+    pub fn press_pek(&mut self, duration_ms: u16) {
+        let pek = field_sets::PekKeyParameters::from([self.registers[0x36]]);
+        if duration_ms >= pek.long_press_time().millis() {
+            self.raise_irq(AxpIrq::PekLongPress);
+        } else {
+            self.raise_irq(AxpIrq::PekShortPress);
+        }
+        if pek.auto_shutdown_if_pek_held_longer_than_shutdown_time()
+            && duration_ms >= pek.shutdown_time().millis()
+        {
+            self.model.powered_off = true;
+        }
+    }
+
+    /// Lets `duration_ms` pass, accumulating the battery current into the
+    /// coulomb counters while they are enabled.
+    pub fn advance_ms(&mut self, duration_ms: u32) {
+        let control = field_sets::CoulombCounterControl::from([self.registers[0xB8]]);
+        let current_ma = self.battery_current_ma();
+        if !control.coulomb_counter_enable() || self.model.coulomb_paused || current_ma == 0 {
+            return;
+        }
+        let sample_rate =
+            field_sets::AdcSampleRateTsPinControl::from([self.registers[0x84]]).adc_sample_rate();
+        // One count is 65536 * 0.5mA for one ADC sample period.
+        let (index, address) = if current_ma > 0 { (0, 0xB0) } else { (1, 0xB4) };
+        let charge =
+            current_ma.unsigned_abs() as u64 * duration_ms as u64 * sample_rate.hz() as u64;
+        let total = self.model.coulomb_remainders[index] + charge;
+        self.model.coulomb_remainders[index] = total % 32_768_000;
+        let start = address as usize;
+        let counter = u32::from_be_bytes(self.registers[start..start + 4].try_into().unwrap());
+        let counter = counter.wrapping_add((total / 32_768_000) as u32);
+        self.registers[start..start + 4].copy_from_slice(&counter.to_be_bytes());
+    }
+
+    /// Whether the chip has shut down, through the PEK, a shutdown request in
+    /// `ShutdownBatChgLedControl` or the battery dropping below VOFF.
+    pub fn is_powered_off(&self) -> bool {
+        self.model.powered_off
+    }
+
+    /// Battery current in mA, positive while charging.
+    pub fn battery_current_ma(&self) -> i32 {
+        if self.model.battery_mv.is_none() {
+            0
+        } else if self.model.charging {
+            let charge = field_sets::ChargeControl1::from([self.registers[0x33]]);
+            charge.charge_current().ma() as i32
+        } else if self.external_supply_mv().is_some() {
+            0
+        } else {
+            -(self.model.system_load_ma as i32)
+        }
+    }
+
+    /// Voltage of the best usable external input.
+    fn external_supply_mv(&self) -> Option<u16> {
+        [self.model.acin, self.model.vbus]
+            .into_iter()
+            .flatten()
+            .filter(SimSupply::usable)
+            .map(|supply| supply.voltage_mv)
+            .max()
+    }
+
+    /// Recomputes the status and ADC registers from the model inputs.
+    fn refresh(&mut self) {
+        let model = &self.model;
+        let external_mv = self.external_supply_mv();
+        let charge = field_sets::ChargeControl1::from([self.registers[0x33]]);
+        let ts_control = field_sets::AdcSampleRateTsPinControl::from([self.registers[0x84]]);
+        let ts_monitored = !ts_control.ts_pin_function_is_external_adc();
+        let too_cold =
+            ts_monitored && model.ts_pin_mv > temp_threshold_mv_from_raw(self.registers[0x38]);
+        let too_hot =
+            ts_monitored && model.ts_pin_mv < temp_threshold_mv_from_raw(self.registers[0x39]);
+
+        let mut charge_done =
+            model.charge_done && model.battery_mv.is_some() && external_mv.is_some();
+        let mut charging = false;
+        if let Some(battery_mv) = model.battery_mv
+            && external_mv.is_some()
+            && charge.charge_enable()
+            && !charge_done
+            && !too_cold
+            && !too_hot
+        {
+            if battery_mv >= charge.target_voltage().mv() {
+                charge_done = true;
+            } else {
+                charging = true;
+            }
+        }
+        let became_charging = charging && !model.charging;
+        let became_done = charge_done && !model.charge_done;
+        self.model.charging = charging;
+        self.model.charge_done = charge_done;
+        if became_charging {
+            self.raise_irq(AxpIrq::ChargeStarted);
+        }
+        if became_done {
+            self.raise_irq(AxpIrq::ChargeDone);
+        }
+        if too_hot && !self.model.battery_too_hot {
+            self.raise_irq(AxpIrq::BatteryOverTemp);
+        }
+        if too_cold && !self.model.battery_too_cold {
+            self.raise_irq(AxpIrq::BatteryUnderTemp);
+        }
+        self.model.battery_too_hot = too_hot;
+        self.model.battery_too_cold = too_cold;
+
+        let aps_mv = external_mv.or(self.model.battery_mv).unwrap_or(0);
+        let level_2_raw = self.registers[0x3B] as u32;
+        let aps_low = aps_mv as u32 * 10 < 28_672 + level_2_raw * 56;
+        if aps_low && !self.model.aps_low {
+            self.raise_irq(AxpIrq::ApsLowVoltageLevel2);
+        }
+        self.model.aps_low = aps_low;
+        let voff = field_sets::ShutdownVoltageSetting::from([self.registers[0x31]]).voff_voltage();
+        if external_mv.is_none()
+            && self
+                .model
+                .battery_mv
+                .is_some_and(|mv| mv < 2600 + voff as u16 * 100)
+        {
+            self.model.powered_off = true;
+        }
+
+        let model = &self.model;
+        let acin = model.acin;
+        let vbus = model.vbus;
+        let vbus_usable = vbus.is_some_and(|vbus| vbus.usable());
+        let battery_current_ma = self.battery_current_ma();
+
+        let mut power_status = self.registers[0x00] & 0x03;
+        power_status |= (acin.is_some() as u8) << 7;
+        power_status |= (acin.is_some_and(|acin| acin.usable()) as u8) << 6;
+        power_status |= (vbus.is_some() as u8) << 5;
+        power_status |= (vbus_usable as u8) << 4;
+        power_status |= (vbus_usable as u8) << 3;
+        power_status |= ((battery_current_ma > 0) as u8) << 2;
+        self.registers[0x00] = power_status;
+
+        let mut charge_status = self.registers[0x01] & 0x02;
+        charge_status |= (model.charging as u8) << 6;
+        charge_status |= (model.battery_mv.is_some() as u8) << 5;
+        self.registers[0x01] = charge_status;
+
+        self.registers[0x04] =
+            ((vbus_usable as u8) << 2) | ((vbus.is_some() as u8) << 1) | (vbus.is_none() as u8);
+
+        let enable_1 = field_sets::AdcEnable1::from([self.registers[0x82]]);
+        let enable_2 = field_sets::AdcEnable2::from([self.registers[0x83]]);
+        let acin = acin.unwrap_or(SimSupply::new(0, 0));
+        let vbus = vbus.unwrap_or(SimSupply::new(0, 0));
+        let battery_mv = model.battery_mv.unwrap_or(0);
+        let die_temperature = model.die_temperature;
+        let ts_pin_mv = model.ts_pin_mv;
+        if enable_1.acin_voltage_adc_enable() {
+            self.set_adc_12bit(0x56, acin.voltage_mv as u32 * 10 / 17);
+        }
+        if enable_1.acin_current_adc_enable() {
+            self.set_adc_12bit(0x58, acin.current_ma as u32 * 1000 / 625);
+        }
+        if enable_1.vbus_voltage_adc_enable() {
+            self.set_adc_12bit(0x5A, vbus.voltage_mv as u32 * 10 / 17);
+        }
+        if enable_1.vbus_current_adc_enable() {
+            self.set_adc_12bit(0x5C, vbus.current_ma as u32 * 1000 / 375);
+        }
+        if enable_2.internal_temperature_adc_enable() {
+            let code = (die_temperature.decidegrees() as i32 + 1447).max(0);
+            self.set_adc_12bit(0x5E, code as u32);
+        }
+        let voltage_code = (battery_mv as u32 * 10 / 11).min(0xFFF);
+        let current_code = (battery_current_ma.unsigned_abs() * 2).min(0x1FFF);
+        if enable_1.battery_voltage_adc_enable() {
+            self.set_adc_12bit(0x78, voltage_code);
+        }
+        if enable_1.battery_current_adc_enable() {
+            let (charge_code, discharge_code) = if battery_current_ma > 0 {
+                (current_code, 0)
+            } else {
+                (0, current_code)
+            };
+            self.set_adc_13bit(0x7A, charge_code);
+            self.set_adc_13bit(0x7C, discharge_code);
+        }
+        if enable_1.battery_voltage_adc_enable() && enable_1.battery_current_adc_enable() {
+            let power = (voltage_code * current_code).to_be_bytes();
+            self.registers[0x70..0x73].copy_from_slice(&power[1..]);
+        }
+        if enable_1.aps_voltage_adc_enable() {
+            self.set_adc_12bit(0x7E, aps_mv as u32 * 10 / 14);
+        }
+        if enable_1.ts_pin_adc_enable() {
+            self.set_adc_12bit(0x62, ts_pin_mv as u32 * 10 / 8);
+        }
+    }
+
+    fn set_adc_12bit(&mut self, address: u8, code: u32) {
+        let code = code.min(0xFFF);
+        self.registers[address as usize] = (code >> 4) as u8;
+        self.registers[address as usize + 1] = (code & 0x0F) as u8;
+    }
+
+    fn set_adc_13bit(&mut self, address: u8, code: u32) {
+        let code = code.min(0x1FFF);
+        self.registers[address as usize] = (code >> 5) as u8;
+        self.registers[address as usize + 1] = (code & 0x1F) as u8;
+    }
+
+    fn bus_write(&mut self, address: u8, value: u8) {
+        let Some(register) = register_map::by_address(address) else {
+            return;
+        };
+        let current = self.registers[address as usize];
+        let new = match register.access {
+            RegisterAccess::ReadOnly => return,
+            RegisterAccess::WriteOneToClear => current & !value,
+            RegisterAccess::ReadWrite => {
+                let read_only = register.read_only_mask;
+                let clear = register.write_one_to_clear_mask;
+                (current & read_only) | (current & clear & !value) | (value & !(read_only | clear))
+            }
+        };
+        self.registers[address as usize] = new;
+
+        match address {
+            COULOMB_COUNTER_CONTROL => {
+                if new & COULOMB_CLEAR_REQUEST != 0 {
+                    self.registers
+                        [COULOMB_COUNTERS_START..COULOMB_COUNTERS_START + COULOMB_COUNTERS_LEN]
+                        .fill(0);
+                }
+                if new & COULOMB_PAUSE_REQUEST != 0 {
+                    self.model.coulomb_paused = true;
+                } else if new & COULOMB_ENABLE != 0 {
+                    self.model.coulomb_paused = false;
+                }
+                self.registers[address as usize] &=
+                    !(COULOMB_CLEAR_REQUEST | COULOMB_PAUSE_REQUEST);
+            }
+            SHUTDOWN_CONTROL if new & SHUTDOWN_REQUEST != 0 => {
+                self.model.powered_off = true;
+                self.registers[address as usize] &= !SHUTDOWN_REQUEST;
+            }
+            // REG10H[2]/REG10H[0] and REG12H[6]/REG12H[4] are the same EXTEN and DC-DC2 switches.
+            EXTEN_DCDC2_CONTROL => {
+                let shared = ((new & 0x04) << 4) | ((new & 0x01) << 4);
+                let output = &mut self.registers[POWER_OUTPUT_CONTROL as usize];
+                *output = (*output & !0x50) | shared;
+            }
+            POWER_OUTPUT_CONTROL => {
+                let shared = ((new & 0x40) >> 4) | ((new & 0x10) >> 4);
+                let control = &mut self.registers[EXTEN_DCDC2_CONTROL as usize];
+                *control = (*control & !0x05) | shared;
+            }
+            _ => {}
+        }
+    }
+
+    fn run(&mut self, address: u8, operations: &mut [Operation<'_>]) -> Result<(), ErrorKind> {
+        if address != AXP192_I2C_ADDRESS {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    let Some((&pointer, data)) = bytes.split_first() else {
+                        continue;
+                    };
+                    self.pointer = pointer;
+                    for &byte in data {
+                        self.bus_write(self.pointer, byte);
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.registers[self.pointer as usize];
+                        self.pointer = self.pointer.wrapping_add(1);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for SimAxp192 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl embedded_hal::i2c::ErrorType for SimAxp192 {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for SimAxp192 {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.run(address, operations)
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for SimAxp192 {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.run(address, operations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::RefCell;
+    use std::boxed::Box;
+    use std::collections::VecDeque;
+
+    use embedded_hal::i2c::I2c;
+
+    use super::*;
+    use crate::{Axp192, Axp192Async, ChargerState, PekPress};
+
+    /// Shares one chip between a driver and a [`SimIrqPin`].
+    struct SharedSim<'a>(&'a RefCell<SimAxp192>);
+
+    impl embedded_hal::i2c::ErrorType for SharedSim<'_> {
+        type Error = ErrorKind;
+    }
+
+    impl embedded_hal_async::i2c::I2c for SharedSim<'_> {
+        async fn transaction(
+            &mut self,
+            address: u8,
+            operations: &mut [Operation<'_>],
+        ) -> Result<(), Self::Error> {
+            self.0.borrow_mut().run(address, operations)
+        }
+    }
+
+    type SimEvent = Box<dyn FnOnce(&mut SimAxp192)>;
+
+    /// IRQ pin of a shared chip. Each time the awaited level is not there yet,
+    /// the next scripted event is applied to the chip.
+    struct SimIrqPin<'a> {
+        sim: &'a RefCell<SimAxp192>,
+        events: VecDeque<SimEvent>,
+    }
+
+    impl SimIrqPin<'_> {
+        fn wait_for_asserted(&mut self, asserted: bool) {
+            while self.sim.borrow().irq_asserted() != asserted {
+                let event = self.events.pop_front().expect("the pin never changes");
+                event(&mut self.sim.borrow_mut());
+            }
+        }
+    }
+
+    impl embedded_hal::digital::ErrorType for SimIrqPin<'_> {
+        type Error = core::convert::Infallible;
+    }
+
+    impl embedded_hal_async::digital::Wait for SimIrqPin<'_> {
+        async fn wait_for_high(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_asserted(false);
+            Ok(())
+        }
+
+        async fn wait_for_low(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_asserted(true);
+            Ok(())
+        }
+
+        async fn wait_for_rising_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_asserted(true);
+            self.wait_for_asserted(false);
+            Ok(())
+        }
+
+        async fn wait_for_falling_edge(&mut self) -> Result<(), Self::Error> {
+            self.wait_for_asserted(false);
+            self.wait_for_asserted(true);
+            Ok(())
+        }
+
+        async fn wait_for_any_edge(&mut self) -> Result<(), Self::Error> {
+            let asserted = self.sim.borrow().irq_asserted();
+            self.wait_for_asserted(!asserted);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn registers_start_at_reset_values() {
+        let sim = SimAxp192::new();
+        ::core::assert_eq!(sim.register(0x06), 0xF0);
+        ::core::assert_eq!(sim.register(0x12), 0x5F);
+        ::core::assert_eq!(sim.register(0x40), 0xD8);
+        ::core::assert_eq!(sim.register(0x44), 0x00);
+        // Multi-byte registers are stored big-endian.
+        ::core::assert_eq!(sim.register(0x56), 0x00);
+
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        let pek = axp.ll.pek_key_parameters().read().unwrap();
+        ::core::assert_eq!(<[u8; 1]>::from(pek), [0x5D]);
+    }
+
+    #[test]
+    fn irq_status_is_write_one_to_clear() {
+        let mut sim = SimAxp192::new();
+        sim.raise_irq(AxpIrq::VbusInserted);
+        sim.raise_irq(AxpIrq::AcinInserted);
+        // Not enabled at reset, so never latched.
+        sim.raise_irq(AxpIrq::VbusRemoved);
+        ::core::assert!(!sim.irq_pending(AxpIrq::VbusRemoved));
+
+        let mut axp = Axp192::new(&mut sim);
+        axp.clear_irqs(AxpIrq::VbusInserted.into()).unwrap();
+        let pending = axp.read_pending_irqs().unwrap();
+        ::core::assert!(pending.contains(AxpIrq::AcinInserted));
+        ::core::assert!(!pending.contains(AxpIrq::VbusInserted));
+
+        // Writing 0 leaves flags alone.
+        sim.write(AXP192_I2C_ADDRESS, &[0x44, 0x00]).unwrap();
+        ::core::assert!(sim.irq_pending(AxpIrq::AcinInserted));
+        sim.write(AXP192_I2C_ADDRESS, &[0x44, 0xFF]).unwrap();
+        ::core::assert!(!sim.irq_asserted());
+    }
+
+    #[test]
+    fn timer_timeout_flag_is_write_one_to_clear() {
+        let mut sim = SimAxp192::new();
+        sim.set_register(0x8A, 0x85);
+        sim.write(AXP192_I2C_ADDRESS, &[0x8A, 0x0A]).unwrap();
+        ::core::assert_eq!(sim.register(0x8A), 0x8A);
+        sim.write(AXP192_I2C_ADDRESS, &[0x8A, 0x8A]).unwrap();
+        ::core::assert_eq!(sim.register(0x8A), 0x0A);
+    }
+
+    #[test]
+    fn read_only_registers_and_bits_ignore_writes() {
+        let mut sim = SimAxp192::new();
+        sim.set_register(0x00, 0x50);
+        sim.write(AXP192_I2C_ADDRESS, &[0x00, 0xFF]).unwrap();
+        ::core::assert_eq!(sim.register(0x00), 0x50);
+
+        // GPIO0-2 input levels in REG94H are read-only, the output bits are not.
+        sim.set_register(0x94, 0x10);
+        sim.write(AXP192_I2C_ADDRESS, &[0x94, 0x07]).unwrap();
+        ::core::assert_eq!(sim.register(0x94), 0x17);
+
+        let mut axp = Axp192::new(&mut sim);
+        let status = axp.ll.power_status().read().unwrap();
+        ::core::assert!(status.acin_usable());
+        ::core::assert!(!status.vbus_present());
+    }
+
+    #[test]
+    fn burst_access_auto_increments() {
+        let mut sim = SimAxp192::new();
+        sim.write(AXP192_I2C_ADDRESS, &[0x06, 1, 2, 3, 4, 5, 6])
+            .unwrap();
+        let mut data = [0u8; 6];
+        sim.write_read(AXP192_I2C_ADDRESS, &[0x06], &mut data)
+            .unwrap();
+        ::core::assert_eq!(data, [1, 2, 3, 4, 5, 6]);
+        ::core::assert!(sim.write(0x35, &[0x06, 0]).is_err());
+    }
+
+    #[test]
+    fn coulomb_clear_request_zeroes_counters() {
+        let mut sim = SimAxp192::new();
+        sim.set_registers(0xB0, &[0, 0, 1, 0, 0, 0, 0, 0x80]);
+        let mut axp = Axp192::new(&mut sim);
+        axp.set_coulomb_counter_enable(true).unwrap();
+        ::core::assert_eq!(axp.read_coulomb_counters().unwrap().charge, 0x100);
+
+        axp.clear_coulomb_counter().unwrap();
+        let counts = axp.read_coulomb_counters().unwrap();
+        ::core::assert_eq!((counts.charge, counts.discharge), (0, 0));
+        // The request bit clears itself; the enable stays set.
+        ::core::assert_eq!(sim.register(0xB8), 0x80);
+    }
+
+    #[test]
+    fn exten_and_dcdc2_enables_are_shared() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        axp.set_dcdc_enable(crate::DcId::Dcdc2, false).unwrap();
+        ::core::assert_eq!(sim.register(0x10) & 0x01, 0);
+    }
+
+    #[test]
+    fn charging_runs_until_the_target_voltage() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        axp.enable_irqs(AxpIrq::ChargeStarted | AxpIrq::ChargeDone)
+            .unwrap();
+        axp.ll
+            .adc_enable_1()
+            .modify(|r| r.set_battery_current_adc_enable(true))
+            .unwrap();
+        sim.set_battery(Some(3900));
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.charger_state().unwrap(), ChargerState::Discharging);
+
+        sim.set_vbus(Some(SimSupply::new(5000, 500)));
+        ::core::assert!(sim.irq_pending(AxpIrq::ChargeStarted));
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert!(axp.charger_state().unwrap().is_charging());
+        // Reset charge current.
+        ::core::assert_eq!(axp.get_battery_charge_current().unwrap().ma(), 780);
+
+        sim.set_battery(Some(4200));
+        ::core::assert!(sim.irq_pending(AxpIrq::ChargeDone));
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.charger_state().unwrap(), ChargerState::Done);
+        ::core::assert_eq!(axp.get_battery_charge_current().unwrap().ma(), 0);
+
+        sim.set_vbus(None);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.charger_state().unwrap(), ChargerState::Discharging);
+    }
+
+    #[test]
+    fn pek_presses_latch_in_irq_status_3() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        axp.enable_irqs(AxpIrq::PekShortPress | AxpIrq::PekLongPress)
+            .unwrap();
+        ::core::assert_eq!(axp.read_pek_press().unwrap(), None);
+
+        sim.press_pek(200);
+        ::core::assert_eq!(sim.register(0x46), 0x02);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.read_pek_press().unwrap(), Some(PekPress::Short));
+        ::core::assert_eq!(axp.read_pek_press().unwrap(), None);
+
+        // Reset long-press time is 1.5s and shutdown time 6s.
+        sim.press_pek(2000);
+        ::core::assert_eq!(sim.register(0x46), 0x01);
+        ::core::assert!(!sim.is_powered_off());
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.read_pek_press().unwrap(), Some(PekPress::Long));
+
+        sim.press_pek(6000);
+        ::core::assert!(sim.is_powered_off());
+    }
+
+    #[test]
+    fn adc_registers_follow_the_inputs() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        // Only the battery voltage, APS and TS pin channels are enabled at reset.
+        axp.ll
+            .adc_enable_1()
+            .modify(|r| r.set_battery_current_adc_enable(true))
+            .unwrap();
+        axp.ll
+            .adc_enable_2()
+            .modify(|r| r.set_internal_temperature_adc_enable(true))
+            .unwrap();
+        sim.set_battery(Some(3700));
+        sim.set_system_load_ma(250);
+        sim.set_die_temperature(DeciCelsius(400));
+        let mut axp = Axp192::new(&mut sim);
+        // The battery voltage ADC has a 1.1mV step.
+        ::core::assert_eq!(axp.get_battery_voltage().unwrap().mv(), 3699);
+        ::core::assert_eq!(axp.get_battery_discharge_current().unwrap().ma(), 250);
+        ::core::assert_eq!(axp.get_internal_temperature().unwrap(), DeciCelsius(400));
+
+        // Channels switched off keep their last value.
+        axp.ll
+            .adc_enable_1()
+            .modify(|r| r.set_battery_voltage_adc_enable(false))
+            .unwrap();
+        sim.set_battery(Some(3500));
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.get_battery_voltage().unwrap().mv(), 3699);
+    }
+
+    #[test]
+    fn coulomb_counters_integrate_the_battery_current() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3800));
+        sim.set_system_load_ma(500);
+        let mut axp = Axp192::new(&mut sim);
+        axp.set_coulomb_counter_enable(true).unwrap();
+
+        sim.advance_ms(3_600_000);
+        let mut axp = Axp192::new(&mut sim);
+        let net = axp.get_coulomb_net_charge().unwrap().uah();
+        // Whole counts only, so the result is slightly under 500mAh.
+        ::core::assert!((-500_000..-499_000).contains(&net), "{net}");
+        ::core::assert_eq!(axp.read_coulomb_counters().unwrap().charge, 0);
+
+        axp.pause_coulomb_counter().unwrap();
+        sim.advance_ms(3_600_000);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.get_coulomb_net_charge().unwrap().uah(), net);
+
+        axp.set_coulomb_counter_enable(true).unwrap();
+        sim.advance_ms(60_000);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert!(axp.get_coulomb_net_charge().unwrap().uah() < net);
+    }
+
+    #[test]
+    fn low_battery_shuts_the_chip_down() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3700));
+        let mut axp = Axp192::new(&mut sim);
+        axp.enable_irqs(AxpIrq::ApsLowVoltageLevel2.into()).unwrap();
+        axp.set_voff_mv(3000).unwrap();
+
+        sim.set_battery(Some(3300));
+        ::core::assert!(sim.irq_pending(AxpIrq::ApsLowVoltageLevel2));
+        ::core::assert!(!sim.is_powered_off());
+        sim.set_battery(Some(2950));
+        ::core::assert!(sim.is_powered_off());
+    }
+
+    #[test]
+    fn restoring_a_snapshot_keeps_backup_data_and_timer() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        let awake = axp.save_snapshot().unwrap();
+        axp.write_backup_data(&[1, 2, 3, 4, 5, 6]).unwrap();
+        axp.set_ldo_enable(crate::LdoId::Ldo2, false).unwrap();
+        sim.set_register(0x8A, 0x85);
+
+        let mut axp = Axp192::new(&mut sim);
+        axp.restore_snapshot(&awake).unwrap();
+        ::core::assert_eq!(sim.register(0x12), 0x5F);
+        ::core::assert_eq!(sim.register(0x8A), 0x85);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.read_backup_data().unwrap(), [1, 2, 3, 4, 5, 6]);
+        ::core::assert_eq!(awake.get(0x06), None);
+        ::core::assert_eq!(awake.get(0x8A), None);
+    }
+
+    #[test]
+    fn wait_for_press_leaves_other_irq_sources_alone() {
+        let sim = RefCell::new(SimAxp192::new());
+        let mut pin = SimIrqPin {
+            sim: &sim,
+            events: VecDeque::from([
+                Box::new(|sim: &mut SimAxp192| sim.set_vbus(Some(SimSupply::new(5000, 100))))
+                    as SimEvent,
+                Box::new(|sim: &mut SimAxp192| sim.press_pek(200)),
+            ]),
+        };
+        let mut axp = Axp192Async::new(SharedSim(&sim));
+        let enabled = embassy_futures::block_on(axp.read_enabled_irqs()).unwrap();
+
+        // VBUS insertion pulls the pin low first.
+        let press = embassy_futures::block_on(axp.wait_for_press(&mut pin)).unwrap();
+        ::core::assert_eq!(press, None);
+        ::core::assert!(sim.borrow().irq_pending(AxpIrq::VbusInserted));
+        ::core::assert_eq!(
+            embassy_futures::block_on(axp.read_enabled_irqs()).unwrap(),
+            enabled | AxpIrq::PekShortPress | AxpIrq::PekLongPress
+        );
+
+        embassy_futures::block_on(axp.clear_irqs(AxpIrq::VbusInserted | AxpIrq::VbusValid))
+            .unwrap();
+        let press = embassy_futures::block_on(axp.wait_for_press(&mut pin)).unwrap();
+        ::core::assert_eq!(press, Some(PekPress::Short));
+        ::core::assert!(pin.events.is_empty());
+        ::core::assert!(!sim.borrow().irq_asserted());
+    }
+
+    #[test]
+    fn event_stream_reports_and_clears_enabled_irqs_only() {
+        let sim = RefCell::new(SimAxp192::new());
+        // Latches BatteryInserted, which is enabled at reset.
+        sim.borrow_mut().set_battery(Some(3700));
+        let pin = SimIrqPin {
+            sim: &sim,
+            events: VecDeque::from([Box::new(|sim: &mut SimAxp192| {
+                sim.set_vbus(Some(SimSupply::new(5000, 100)))
+            }) as SimEvent]),
+        };
+        let mut axp = Axp192Async::new(SharedSim(&sim));
+        embassy_futures::block_on(axp.set_enabled_irqs(AxpIrq::VbusInserted.into())).unwrap();
+        let mut events = crate::AxpEventStream::new(axp, pin);
+
+        let event = embassy_futures::block_on(events.next_event()).unwrap();
+        ::core::assert_eq!(event, AxpIrq::VbusInserted);
+        ::core::assert!(events.queued().is_empty());
+        let sim = sim.borrow();
+        ::core::assert!(!sim.irq_pending(AxpIrq::VbusInserted));
+        // Latched before its source was disabled.
+        ::core::assert!(sim.irq_pending(AxpIrq::BatteryInserted));
+    }
+
+    #[test]
+    fn async_pins_wait_for_the_driver_mutex() {
+        use crate::gpio::{AxpGpiosAsync, GpioId, GpioMode};
+        use embassy_futures::join::join;
+        use embassy_futures::yield_now;
+
+        let mut sim = SimAxp192::new();
+        let axp = crate::AxpMutex::new(Axp192Async::new(&mut sim));
+        let mut pins = AxpGpiosAsync::new(&axp);
+        let (held, pin) = embassy_futures::block_on(join(
+            async {
+                let mut driver = axp.lock().await;
+                yield_now().await;
+                driver
+                    .set_gpio_mode(GpioId::Gpio1, GpioMode::OpenDrainOutput)
+                    .await?;
+                driver.set_gpio_output(GpioId::Gpio1, false).await
+            },
+            async {
+                pins.gpio0.set_mode(GpioMode::OpenDrainOutput).await?;
+                pins.gpio0.set_high().await
+            },
+        ));
+        held.unwrap();
+        pin.unwrap();
+        let mut driver = embassy_futures::block_on(axp.lock());
+        ::core::assert!(embassy_futures::block_on(driver.get_gpio_output(GpioId::Gpio0)).unwrap());
+        ::core::assert!(!embassy_futures::block_on(driver.get_gpio_output(GpioId::Gpio1)).unwrap());
+    }
+
+    #[test]
+    fn pwm_channel_resolves_a_period_above_31_counts() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        // 2.25MHz / 2 / 200 = 5625Hz, with a duty cycle of 31 / 200.
+        let parameters = crate::PwmParameters {
+            x: 1,
+            y1: 200,
+            y2: 31,
+        };
+        axp.set_pwm_parameters(crate::PwmId::Pwm1, &parameters)
+            .unwrap();
+        let axp = RefCell::new(axp);
+        let pwm = crate::pwm::AxpPwm::new(&axp, crate::PwmId::Pwm1).unwrap();
+        ::core::assert!(pwm.max_duty_cycle() <= 31);
+        let parameters = axp
+            .borrow_mut()
+            .get_pwm_parameters(crate::PwmId::Pwm1)
+            .unwrap();
+        ::core::assert_eq!(parameters.y1 as u16, pwm.max_duty_cycle());
+        ::core::assert!(parameters.frequency_hz().abs_diff(5625) < 100);
+        ::core::assert_eq!(
+            parameters.y2 as u16,
+            (31 * parameters.y1 as u16 + 100) / 200
+        );
+    }
+
+    #[test]
+    fn charge_termination_takes_10_or_15_percent() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        axp.set_charge_termination_percent(15).unwrap();
+        ::core::assert!(::core::matches!(
+            axp.set_charge_termination_percent(20),
+            Err(crate::AxpError::InvalidPercentage(20))
+        ));
+        ::core::assert_eq!(sim.register(0x33) & 0x10, 0x10);
+    }
+
+    #[test]
+    fn apply_config_round_trips_through_read_config() {
+        let mut sim = SimAxp192::new();
+        let mut axp = Axp192::new(&mut sim);
+        let mut config = crate::Board::Core2.power_config();
+        config.charger = config.charger.map(|charger| crate::ChargerConfig {
+            current_ma: 1320,
+            ..charger
+        });
+        axp.apply_config(&config).unwrap();
+        ::core::assert_eq!(axp.read_config().unwrap(), config);
+
+        let registers = sim.registers;
+        let mut axp = Axp192::new(&mut sim);
+        let read_back = axp.read_config().unwrap();
+        axp.apply_config(&read_back).unwrap();
+        ::core::assert_eq!(sim.registers, registers);
+    }
+
+    #[test]
+    fn battery_temperature_fault_follows_the_ts_pin() {
+        let mut sim = SimAxp192::new();
+        sim.set_battery(Some(3700));
+        sim.set_vbus(Some(SimSupply::new(5000, 500)));
+        let mut axp = Axp192::new(&mut sim);
+        axp.set_enabled_irqs(crate::AxpIrqSet::empty()).unwrap();
+        ::core::assert_eq!(axp.charger_state().unwrap(), ChargerState::ConstantCurrent);
+
+        // Above the 2112mV low-temperature threshold: too cold to charge.
+        sim.set_ts_pin_mv(2500);
+        ::core::assert!(!sim.irq_pending(AxpIrq::BatteryUnderTemp));
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(
+            axp.charger_state().unwrap(),
+            ChargerState::Fault(crate::ChargerFault::BatteryTemperature)
+        );
+
+        sim.set_ts_pin_mv(800);
+        let mut axp = Axp192::new(&mut sim);
+        ::core::assert_eq!(axp.charger_state().unwrap(), ChargerState::ConstantCurrent);
+    }
+}
@@ -0,0 +1,179 @@
+//! Saving, restoring and comparing the chip configuration.
+//!
+//! A [`RegisterSnapshot`] holds the read-write registers of `device.yaml`; IRQ
+//! status flags and measurement registers are left out. So are the
+//! `DataBuffer` registers, whose backup data must survive restoring a
+//! configuration saved earlier, and `TimerControl`, where writing the saved
+//! value would restart the timer or clear its timeout flag. It serializes to a
+//! [`RegisterSnapshot::BLOB_LEN`]-byte blob for flash or NVS: a format version,
+//! the register values in address order and a CRC-8 over both.
+//! `Axp192::save_snapshot` and `Axp192::restore_snapshot` move it to and from
+//! the chip, and [`RegisterSnapshot::diff`] lists the fields that differ between
+//! two snapshots, e.g. a "sleep" and an "active" configuration.
+
+use core::fmt;
+
+use crate::backup::{BACKUP_DATA_LEN, DATA_BUFFER_START};
+use crate::crc::crc8;
+use crate::register_map::{self, REGISTERS, RegisterAccess, RegisterInfo};
+
+/// Number of registers in a snapshot.
+pub const SNAPSHOT_REGISTER_COUNT: usize = count_snapshot_registers();
+
+/// The registers in a snapshot, sorted by address.
+pub(crate) const SNAPSHOT_REGISTERS: [RegisterInfo; SNAPSHOT_REGISTER_COUNT] = snapshot_registers();
+
+/// Read-write registers that hold configuration only: writing back a value
+/// read earlier neither loses data nor triggers an action.
+const fn is_snapshot_register(info: &RegisterInfo) -> bool {
+    let is_backup_data = info.address >= DATA_BUFFER_START
+        && info.address < DATA_BUFFER_START + BACKUP_DATA_LEN as u8;
+    matches!(info.access, RegisterAccess::ReadWrite)
+        && info.write_one_to_clear_mask == 0
+        && !is_backup_data
+}
+
+const fn count_snapshot_registers() -> usize {
+    let mut count = 0;
+    let mut i = 0;
+    while i < REGISTERS.len() {
+        if is_snapshot_register(&REGISTERS[i]) {
+            count += 1;
+        }
+        i += 1;
+    }
+    count
+}
+
+const fn snapshot_registers() -> [RegisterInfo; SNAPSHOT_REGISTER_COUNT] {
+    let mut registers = [REGISTERS[0]; SNAPSHOT_REGISTER_COUNT];
+    let mut count = 0;
+    let mut i = 0;
+    while i < REGISTERS.len() {
+        if is_snapshot_register(&REGISTERS[i]) {
+            // Every read-write register is one byte wide, so a snapshot stores one byte each.
+            ::core::assert!(REGISTERS[i].size_bytes == 1);
+            registers[count] = REGISTERS[i];
+            count += 1;
+        }
+        i += 1;
+    }
+    registers
+}
+
+/// Why a blob could not be loaded as a [`RegisterSnapshot`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum SnapshotError {
+    #[error("Snapshot blob is {0} bytes long")]
+    InvalidLength(usize),
+    #[error("Unsupported snapshot format version {0}")]
+    UnsupportedVersion(u8),
+    #[error("Snapshot checksum mismatch")]
+    ChecksumMismatch,
+}
+
+/// Contents of the configuration registers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterSnapshot {
+    values: [u8; SNAPSHOT_REGISTER_COUNT],
+}
+
+impl RegisterSnapshot {
+    /// Format version written as the first byte of the blob.
+    pub const FORMAT_VERSION: u8 = 2;
+    /// Length of the serialized snapshot.
+    pub const BLOB_LEN: usize = SNAPSHOT_REGISTER_COUNT + 2;
+
+    pub(crate) const fn from_values(values: [u8; SNAPSHOT_REGISTER_COUNT]) -> Self {
+        Self { values }
+    }
+
+    /// The saved value of the register at `address`, `None` if it is not part of a snapshot.
+    pub fn get(&self, address: u8) -> Option<u8> {
+        self.iter()
+            .find(|&(entry, _)| entry == address)
+            .map(|(_, value)| value)
+    }
+
+    /// `(address, value)` of every register, sorted by address.
+    pub fn iter(&self) -> impl Iterator<Item = (u8, u8)> + '_ {
+        SNAPSHOT_REGISTERS
+            .iter()
+            .zip(self.values.iter())
+            .map(|(info, &value)| (info.address, value))
+    }
+
+    /// Serializes the snapshot as version, register values and CRC-8.
+    pub fn to_bytes(&self) -> [u8; Self::BLOB_LEN] {
+        let mut blob = [0u8; Self::BLOB_LEN];
+        blob[0] = Self::FORMAT_VERSION;
+        blob[1..=SNAPSHOT_REGISTER_COUNT].copy_from_slice(&self.values);
+        blob[Self::BLOB_LEN - 1] = crc8(&blob[..Self::BLOB_LEN - 1]);
+        blob
+    }
+
+    /// Loads a blob written by [`to_bytes`](Self::to_bytes).
+    pub fn from_bytes(blob: &[u8]) -> Result<Self, SnapshotError> {
+        if blob.len() != Self::BLOB_LEN {
+            return Err(SnapshotError::InvalidLength(blob.len()));
+        }
+        if blob[0] != Self::FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(blob[0]));
+        }
+        let (data, crc) = blob.split_at(Self::BLOB_LEN - 1);
+        if crc8(data) != crc[0] {
+            return Err(SnapshotError::ChecksumMismatch);
+        }
+        let mut values = [0u8; SNAPSHOT_REGISTER_COUNT];
+        values.copy_from_slice(&data[1..]);
+        Ok(Self { values })
+    }
+
+    /// Fields whose value differs between `self` and `other`, in register
+    /// order. Read-only status bits are ignored.
+    pub fn diff<'a>(
+        &'a self,
+        other: &'a RegisterSnapshot,
+    ) -> impl Iterator<Item = FieldChange> + 'a {
+        SNAPSHOT_REGISTERS
+            .iter()
+            .zip(self.values.iter().zip(other.values.iter()))
+            .filter(|(_, (old, new))| old != new)
+            .flat_map(|(info, (&old, &new))| {
+                register_map::fields(info.address)
+                    .iter()
+                    .filter(move |field| field.mask() as u8 & !info.read_only_mask != 0)
+                    .filter(move |field| field.extract(old as u32) != field.extract(new as u32))
+                    .map(move |field| FieldChange {
+                        register: info.name,
+                        address: info.address,
+                        field: field.name,
+                        old: field.extract(old as u32),
+                        new: field.extract(new as u32),
+                    })
+            })
+    }
+}
+
+/// A field that differs between two snapshots, with its raw values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldChange {
+    pub register: &'static str,
+    pub address: u8,
+    pub field: &'static str,
+    pub old: u32,
+    pub new: u32,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: {:#x} -> {:#x}",
+            self.register, self.field, self.old, self.new
+        )
+    }
+}
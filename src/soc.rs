@@ -0,0 +1,219 @@
+//! Voltage based state-of-charge estimation.
+//!
+//! A battery's open-circuit voltage (OCV) maps to its state of charge through
+//! a chemistry specific curve. Under load the terminal voltage measured by
+//! `BatteryVoltageAdc` sags (or rises while charging) by `I * R_internal`, so
+//! [`VoltageSocEstimator`] adds that IR drop back before looking up the curve.
+
+use crate::ChargeTargetVoltageValue;
+
+/// One point of an open-circuit-voltage curve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OcvPoint {
+    pub voltage_mv: u16,
+    pub percent: u8,
+}
+
+impl OcvPoint {
+    pub const fn new(voltage_mv: u16, percent: u8) -> Self {
+        Self {
+            voltage_mv,
+            percent,
+        }
+    }
+}
+
+/// Open-circuit voltage to state-of-charge lookup table.
+///
+/// Points must be sorted by strictly increasing voltage with non-decreasing
+/// percentages of at most 100; [`OcvCurve::new`] checks this, so a bad table in
+/// a `const` fails to compile. Values between points are linearly interpolated
+/// and values outside the table are clamped to its ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct OcvCurve {
+    points: &'static [OcvPoint],
+}
+
+impl OcvCurve {
+    /// Typical 1S LiPo / LiCoO2 pouch cell charged to 4.20V.
+    pub const LIPO_4V20: OcvCurve = OcvCurve::new(&[
+        OcvPoint::new(3270, 0),
+        OcvPoint::new(3610, 5),
+        OcvPoint::new(3690, 10),
+        OcvPoint::new(3710, 15),
+        OcvPoint::new(3730, 20),
+        OcvPoint::new(3750, 25),
+        OcvPoint::new(3770, 30),
+        OcvPoint::new(3790, 35),
+        OcvPoint::new(3800, 40),
+        OcvPoint::new(3820, 45),
+        OcvPoint::new(3840, 50),
+        OcvPoint::new(3850, 55),
+        OcvPoint::new(3870, 60),
+        OcvPoint::new(3910, 65),
+        OcvPoint::new(3950, 70),
+        OcvPoint::new(3980, 75),
+        OcvPoint::new(4020, 80),
+        OcvPoint::new(4080, 85),
+        OcvPoint::new(4110, 90),
+        OcvPoint::new(4150, 95),
+        OcvPoint::new(4200, 100),
+    ]);
+
+    /// Typical 1S high-voltage LiPo (LiHV) cell charged to 4.36V.
+    pub const LIPO_4V36: OcvCurve = OcvCurve::new(&[
+        OcvPoint::new(3300, 0),
+        OcvPoint::new(3620, 5),
+        OcvPoint::new(3690, 10),
+        OcvPoint::new(3710, 15),
+        OcvPoint::new(3730, 20),
+        OcvPoint::new(3750, 25),
+        OcvPoint::new(3770, 30),
+        OcvPoint::new(3790, 35),
+        OcvPoint::new(3810, 40),
+        OcvPoint::new(3830, 45),
+        OcvPoint::new(3850, 50),
+        OcvPoint::new(3880, 55),
+        OcvPoint::new(3920, 60),
+        OcvPoint::new(3960, 65),
+        OcvPoint::new(4010, 70),
+        OcvPoint::new(4060, 75),
+        OcvPoint::new(4110, 80),
+        OcvPoint::new(4170, 85),
+        OcvPoint::new(4230, 90),
+        OcvPoint::new(4290, 95),
+        OcvPoint::new(4360, 100),
+    ]);
+
+    /// Typical 1S cylindrical Li-ion (NMC, e.g. 18650) cell charged to 4.20V.
+    pub const LIION_4V20: OcvCurve = OcvCurve::new(&[
+        OcvPoint::new(3000, 0),
+        OcvPoint::new(3450, 5),
+        OcvPoint::new(3540, 10),
+        OcvPoint::new(3620, 20),
+        OcvPoint::new(3670, 30),
+        OcvPoint::new(3720, 40),
+        OcvPoint::new(3780, 50),
+        OcvPoint::new(3850, 60),
+        OcvPoint::new(3920, 70),
+        OcvPoint::new(4000, 80),
+        OcvPoint::new(4100, 90),
+        OcvPoint::new(4200, 100),
+    ]);
+
+    /// Builds a curve from a user supplied table, panicking if it is malformed.
+    // `core::assert!` is spelled out because the crate-level `assert!` may
+    // forward to `defmt`, which is not usable in const context.
+    pub const fn new(points: &'static [OcvPoint]) -> Self {
+        ::core::assert!(!points.is_empty(), "OCV curve needs at least one point");
+        let mut i = 0;
+        while i < points.len() {
+            ::core::assert!(points[i].percent <= 100, "OCV percent above 100");
+            if i > 0 {
+                ::core::assert!(
+                    points[i].voltage_mv > points[i - 1].voltage_mv,
+                    "OCV voltages must be strictly increasing"
+                );
+                ::core::assert!(
+                    points[i].percent >= points[i - 1].percent,
+                    "OCV percentages must not decrease"
+                );
+            }
+            i += 1;
+        }
+        Self { points }
+    }
+
+    /// The built-in LiPo curve matching a `ChargeControl1` target voltage.
+    pub const fn for_charge_target(target: ChargeTargetVoltageValue) -> Self {
+        match target {
+            ChargeTargetVoltageValue::V436 => Self::LIPO_4V36,
+            _ => Self::LIPO_4V20,
+        }
+    }
+
+    pub const fn points(&self) -> &'static [OcvPoint] {
+        self.points
+    }
+
+    /// State of charge in permille for an open-circuit voltage.
+    pub fn soc_permille(&self, ocv_mv: u16) -> u16 {
+        let first = self.points[0];
+        if ocv_mv <= first.voltage_mv {
+            return first.percent as u16 * 10;
+        }
+        for pair in self.points.windows(2) {
+            let (lo, hi) = (pair[0], pair[1]);
+            if ocv_mv <= hi.voltage_mv {
+                let span_mv = (hi.voltage_mv - lo.voltage_mv) as u32;
+                let span_permille = (hi.percent - lo.percent) as u32 * 10;
+                let offset = (ocv_mv - lo.voltage_mv) as u32 * span_permille / span_mv;
+                return lo.percent as u16 * 10 + offset as u16;
+            }
+        }
+        self.points[self.points.len() - 1].percent as u16 * 10
+    }
+
+    /// State of charge in percent for an open-circuit voltage.
+    pub fn soc_percent(&self, ocv_mv: u16) -> u8 {
+        ((self.soc_permille(ocv_mv) + 5) / 10) as u8
+    }
+}
+
+/// Maps a loaded battery voltage to state of charge, compensating for IR drop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct VoltageSocEstimator {
+    curve: OcvCurve,
+    internal_resistance_mohm: u16,
+}
+
+impl VoltageSocEstimator {
+    /// An estimator without IR compensation.
+    pub const fn new(curve: OcvCurve) -> Self {
+        Self {
+            curve,
+            internal_resistance_mohm: 0,
+        }
+    }
+
+    /// Sets the battery internal resistance (plus any series resistance of
+    /// wiring and protection circuit) used for IR drop compensation.
+    pub const fn with_internal_resistance_mohm(mut self, resistance_mohm: u16) -> Self {
+        self.internal_resistance_mohm = resistance_mohm;
+        self
+    }
+
+    pub const fn curve(&self) -> OcvCurve {
+        self.curve
+    }
+
+    pub const fn internal_resistance_mohm(&self) -> u16 {
+        self.internal_resistance_mohm
+    }
+
+    /// Estimated open-circuit voltage. `battery_current_ma` is positive while
+    /// charging and negative while discharging.
+    pub fn open_circuit_voltage_mv(&self, battery_mv: u16, battery_current_ma: i32) -> u16 {
+        let ir_drop_mv = battery_current_ma as i64 * self.internal_resistance_mohm as i64 / 1000;
+        (battery_mv as i64 - ir_drop_mv).clamp(0, u16::MAX as i64) as u16
+    }
+
+    pub fn soc_permille(&self, battery_mv: u16, battery_current_ma: i32) -> u16 {
+        self.curve
+            .soc_permille(self.open_circuit_voltage_mv(battery_mv, battery_current_ma))
+    }
+
+    pub fn soc_percent(&self, battery_mv: u16, battery_current_ma: i32) -> u8 {
+        self.curve
+            .soc_percent(self.open_circuit_voltage_mv(battery_mv, battery_current_ma))
+    }
+}
+
+impl Default for VoltageSocEstimator {
+    fn default() -> Self {
+        Self::new(OcvCurve::LIPO_4V20)
+    }
+}
@@ -0,0 +1,146 @@
+//! Integer measurement types returned by the ADC getters.
+//!
+//! These keep measurements in fixed-point integers so reading the PMIC does
+//! not pull soft-float code into FPU-less targets, and so values can be
+//! printed with `defmt` without float formatting.
+
+use core::fmt;
+
+/// A voltage in millivolts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Millivolts(pub u32);
+
+/// A current in microamps. Signed so net battery current can express direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Microamps(pub i32);
+
+/// A power in microwatts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Microwatts(pub u32);
+
+/// A temperature in tenths of a degree Celsius.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DeciCelsius(pub i16);
+
+/// A charge in microamp-hours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct MicroampHours(pub i64);
+
+impl Millivolts {
+    pub const fn mv(self) -> u32 {
+        self.0
+    }
+}
+
+impl Microamps {
+    pub const fn ua(self) -> i32 {
+        self.0
+    }
+
+    /// The current in whole milliamps, truncated towards zero.
+    pub const fn ma(self) -> i32 {
+        self.0 / 1000
+    }
+}
+
+impl Microwatts {
+    pub const fn uw(self) -> u32 {
+        self.0
+    }
+
+    /// The power in whole milliwatts, truncated.
+    pub const fn mw(self) -> u32 {
+        self.0 / 1000
+    }
+}
+
+impl DeciCelsius {
+    pub const fn decidegrees(self) -> i16 {
+        self.0
+    }
+
+    /// The temperature in whole degrees Celsius, truncated towards zero.
+    pub const fn degrees(self) -> i16 {
+        self.0 / 10
+    }
+}
+
+impl MicroampHours {
+    pub const fn uah(self) -> i64 {
+        self.0
+    }
+
+    /// The charge in whole milliamp-hours, truncated towards zero.
+    pub const fn mah(self) -> i64 {
+        self.0 / 1000
+    }
+}
+
+impl fmt::Display for Millivolts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}mV", self.0)
+    }
+}
+
+impl fmt::Display for Microamps {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}uA", self.0)
+    }
+}
+
+impl fmt::Display for Microwatts {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}uW", self.0)
+    }
+}
+
+impl fmt::Display for DeciCelsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        write!(f, "{}{}.{}C", sign, abs / 10, abs % 10)
+    }
+}
+
+impl fmt::Display for MicroampHours {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}uAh", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Millivolts {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u32}mV", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Microamps {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=i32}uA", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Microwatts {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=u32}uW", self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DeciCelsius {
+    fn format(&self, f: defmt::Formatter) {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let abs = self.0.unsigned_abs();
+        defmt::write!(f, "{=str}{=u16}.{=u16}C", sign, abs / 10, abs % 10)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for MicroampHours {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=i64}uAh", self.0)
+    }
+}